@@ -1,19 +1,65 @@
-use crate::callgraph::{CallGraph, NodeId};
-use crate::info::{FunctionsInfo, InterningInfo, MacrosInfo, ModulesInfo, TypeInfo};
+use crate::callgraph::{CallGraph, CallSite, EdgeKind, NodeId};
+use crate::dead_code::DeadCodeReport;
+use crate::ffi::FfiReport;
+use crate::info::{
+    AggregatesInfo, AssertsInfo, CallSitesInfo, FunctionsInfo, InterningInfo, MacrosInfo,
+    ModulesInfo, TypeInfo, UnsafeCodeInfo,
+};
+use crate::panics::PanicReachabilityReport;
+use crate::query::EdgeFilter;
 use crate::types::TypeHierarchy;
+use crate::unsafe_reachability::UnsafeReachabilityReport;
 use corpus_database::tables::Tables;
 use corpus_database::types::*;
 use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+/// The algorithm that resolves the targets of virtual calls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VirtualCallResolution {
+    /// Class Hierarchy Analysis: a call resolves to the method of every impl of the trait.
+    Cha,
+    /// Rapid Type Analysis: a call resolves only to the methods of impls whose self type is
+    /// constructed in some function that is reachable from an entry point. ADTs that are
+    /// defined outside the analysed packages or that are never constructed by an aggregate,
+    /// e.g., in std, by ctor shims or in promoted constants, are treated as constructed.
+    Rta,
+}
+
+impl FromStr for VirtualCallResolution {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cha" => Ok(VirtualCallResolution::Cha),
+            "rta" => Ok(VirtualCallResolution::Rta),
+            _ => Err(format!("Unknown virtual call resolution: {}", s)),
+        }
+    }
+}
 
 pub struct CallGraphAnalysis<'a> {
     // Generic calls.
     generic_calls: HashSet<FunctionCall>,
     // Dynamic dispatch calls.
     virtual_calls: HashSet<FunctionCall>,
+    // Mapping from virtual call to the principal trait of its `dyn` receiver.
+    virtual_call_receivers: HashMap<FunctionCall, DefPath>,
     // Call-graph.
     call_graph: Vec<(FunctionCall, DefPath, DefPath)>,
     // Mapping from generic function to its instantiations.
     generic_calls_instantiations: HashMap<FunctionCall, Vec<DefPath>>,
+    // Calls through function pointers and `dyn Fn*` objects: (caller, called type, signature).
+    indirect_calls: Vec<(FunctionCall, DefPath, Type, InternedString)>,
+    // Mapping from signature to the functions and closures that are coerced to function pointers.
+    function_pointer_targets: HashMap<InternedString, HashSet<DefPath>>,
+    // Mapping from signature to the closures that have it.
+    closure_signatures: HashMap<InternedString, HashSet<DefPath>>,
+    resolution: VirtualCallResolution,
+    // ADTs constructed by each function, needed only by RTA.
+    aggregates: Option<AggregatesInfo>,
+    call_sites: CallSitesInfo<'a>,
+    unsafe_code: UnsafeCodeInfo,
+    asserts: AssertsInfo,
     types: TypeInfo,
     functions: FunctionsInfo<'a>,
     macros: MacrosInfo<'a>,
@@ -22,7 +68,7 @@ pub struct CallGraphAnalysis<'a> {
 }
 
 impl<'a> CallGraphAnalysis<'a> {
-    pub fn new(tables: &'a Tables) -> Self {
+    pub fn new(tables: &'a Tables, resolution: VirtualCallResolution) -> Self {
         let mut generic_calls = HashSet::new();
         for (call,) in tables.relations.generic_calls.iter() {
             generic_calls.insert(*call);
@@ -31,6 +77,10 @@ impl<'a> CallGraphAnalysis<'a> {
         for (call,) in tables.relations.virtual_calls.iter() {
             virtual_calls.insert(*call);
         }
+        let mut virtual_call_receivers = HashMap::new();
+        for (call, trait_def_path) in tables.relations.virtual_call_receivers.iter() {
+            virtual_call_receivers.insert(*call, *trait_def_path);
+        }
         let mut call_graph = Vec::new();
         for (call_id, caller, callee) in tables.relations.call_graph.iter() {
             call_graph.push((*call_id, *caller, *callee));
@@ -43,11 +93,41 @@ impl<'a> CallGraphAnalysis<'a> {
                 generic_calls_instantiations.insert(*call_id, vec![*instantiation]);
             }
         }
+        let mut indirect_calls = Vec::new();
+        for (call_id, caller, func_type, signature) in tables.relations.indirect_calls.iter() {
+            indirect_calls.push((*call_id, *caller, *func_type, *signature));
+        }
+        let mut function_pointer_targets: HashMap<_, HashSet<_>> = HashMap::new();
+        for (def_path, signature) in tables.relations.function_pointer_targets.iter() {
+            function_pointer_targets
+                .entry(*signature)
+                .or_default()
+                .insert(*def_path);
+        }
+        let mut closure_signatures: HashMap<_, HashSet<_>> = HashMap::new();
+        for (def_path, signature) in tables.relations.closure_signatures.iter() {
+            closure_signatures
+                .entry(*signature)
+                .or_default()
+                .insert(*def_path);
+        }
         Self {
             generic_calls,
             virtual_calls,
+            virtual_call_receivers,
             call_graph,
             generic_calls_instantiations,
+            indirect_calls,
+            function_pointer_targets,
+            closure_signatures,
+            resolution,
+            aggregates: match resolution {
+                VirtualCallResolution::Cha => None,
+                VirtualCallResolution::Rta => Some(AggregatesInfo::new(tables)),
+            },
+            call_sites: CallSitesInfo::new(tables),
+            unsafe_code: UnsafeCodeInfo::new(tables),
+            asserts: AssertsInfo::new(tables),
             types: TypeInfo::new(tables),
             functions: FunctionsInfo::new(tables),
             macros: MacrosInfo::new(tables),
@@ -87,6 +167,10 @@ impl<'a> CallGraphAnalysis<'a> {
                     .functions
                     .is_externally_visible(def_path, &self.modules, &self.types),
             };
+            let details = match is_macro {
+                true => None,
+                false => self.functions.function_details(def_path, &self.types),
+            };
             callgraph.add_node(
                 def_path,
                 package_info,
@@ -96,10 +180,15 @@ impl<'a> CallGraphAnalysis<'a> {
                 num_lines,
                 is_macro,
                 source_location,
+                details,
             )
         }
     }
-    fn add_function_calls_to_callgraph(&self, callgraph: &mut CallGraph) {
+    fn add_function_calls_to_callgraph(
+        &self,
+        callgraph: &mut CallGraph,
+        unconstructed_adts: Option<&HashSet<DefPath>>,
+    ) {
         // Add function definitions into the callgraph.
         for def_path in self.functions.iter_def_paths() {
             self.add_function_to_callgraph(callgraph, &def_path);
@@ -107,77 +196,270 @@ impl<'a> CallGraphAnalysis<'a> {
         // Analyze function calls and extend the callgraph accordingly.
         for (call_id, caller, callee) in self.call_graph.iter() {
             let caller_id = self.add_function_to_callgraph(callgraph, &caller);
-            if self.virtual_calls.contains(&call_id) {
-                match self.resolve_virtual_call(&callee) {
-                    Ok(resolved_callees) => {
-                        for callee in resolved_callees {
-                            let callee_id = self.add_function_to_callgraph(callgraph, &callee);
-                            callgraph.add_virtual_function_call_edge(caller_id, callee_id);
-                        }
-                    }
-                    Err(_) => {}
-                    // Err(error) => println!("Resoltion failed: {}", error),
+            let call_site = self.call_site(call_id);
+            let resolved_callees = self.resolve_call(call_id, callee, unconstructed_adts);
+            // A default method is resolved once for every impl that inherits it, but the call
+            // is counted once.
+            let mut added_edges = HashSet::new();
+            for (callee, kind, inheriting_impl) in resolved_callees {
+                let callee_id = self.add_function_to_callgraph(callgraph, &callee);
+                if added_edges.insert((callee_id, kind)) {
+                    callgraph.add_function_call_edge(caller_id, callee_id, kind, call_site.clone());
                 }
-            } else if self.generic_calls.contains(&call_id) {
-                // Add concrete (static dispatch) calls.
-                let mut instantiations_set: HashSet<DefPath> = HashSet::new();
-                if let Some(instantiations) = self.generic_calls_instantiations.get(&call_id) {
-                    for inst in instantiations {
-                        let inst_id = self.add_function_to_callgraph(callgraph, inst);
-                        callgraph.add_static_function_call_edge(caller_id, inst_id);
-                        instantiations_set.insert(*inst);
+                if let Some(trait_impl) = inheriting_impl {
+                    let (_, typ) = self.types.get_impl_types(&trait_impl);
+                    let (implementing_type, _) = self.types.resolve_type(&typ, &self.interning);
+                    callgraph.add_implementing_type(caller_id, callee_id, kind, implementing_type);
+                }
+            }
+        }
+        for (call_id, caller, func_type, signature) in &self.indirect_calls {
+            let caller_id = self.add_function_to_callgraph(callgraph, caller);
+            let call_site = self.call_site(call_id);
+            for callee in self.resolve_indirect_call(func_type, signature) {
+                let callee_id = self.add_function_to_callgraph(callgraph, callee);
+                callgraph.add_function_call_edge(
+                    caller_id,
+                    callee_id,
+                    EdgeKind::Indirect,
+                    call_site.clone(),
+                );
+            }
+        }
+    }
+    fn call_site(&self, call_id: &FunctionCall) -> Option<CallSite> {
+        self.call_sites
+            .call_site(call_id)
+            .map(|(location, is_macro_expanded)| CallSite {
+                location,
+                is_macro_expanded,
+            })
+    }
+    // Returns the functions that may be called by the call, how each of them is resolved, and,
+    // for default methods, the impl that inherits the method. If
+    // `unconstructed_adts` is given, virtual calls are not resolved to the impls of these ADTs.
+    fn resolve_call(
+        &self,
+        call_id: &FunctionCall,
+        callee: &DefPath,
+        unconstructed_adts: Option<&HashSet<DefPath>>,
+    ) -> Vec<(DefPath, EdgeKind, Option<DefPath>)> {
+        let mut callees = Vec::new();
+        if self.virtual_calls.contains(&call_id) {
+            let receiver = self.virtual_call_receivers.get(call_id);
+            match self.resolve_virtual_call(&callee, receiver, unconstructed_adts) {
+                Ok(resolved_callees) => {
+                    for (callee, inheriting_impl) in resolved_callees {
+                        let kind = match inheriting_impl {
+                            Some(_) => EdgeKind::DefaultMethod,
+                            None => EdgeKind::Virtual,
+                        };
+                        callees.push((callee, kind, inheriting_impl));
                     }
                 }
-                // Overaproximate non-concrete calls, i.e., treat call as virtual.
-                match self.resolve_virtual_call(&callee) {
-                    Ok(resolved_callees) => {
-                        for callee in resolved_callees {
-                            if instantiations_set.get(&callee).is_none() {
-                                // Add only if there is no concrete call already added.
-                                let callee_id = self.add_function_to_callgraph(callgraph, &callee);
-                                callgraph.add_virtual_function_call_edge(caller_id, callee_id);
+                Err(_) => {}
+                // Err(error) => println!("Resoltion failed: {}", error),
+            }
+        } else if self.generic_calls.contains(&call_id) {
+            // Add concrete (static dispatch) calls.
+            let mut instantiations_set: HashSet<DefPath> = HashSet::new();
+            if let Some(instantiations) = self.generic_calls_instantiations.get(&call_id) {
+                for inst in instantiations {
+                    callees.push((*inst, EdgeKind::Instance, None));
+                    instantiations_set.insert(*inst);
+                }
+            }
+            // Overaproximate non-concrete calls, i.e., treat call as virtual.
+            match self.resolve_virtual_call(&callee, None, unconstructed_adts) {
+                Ok(resolved_callees) => {
+                    for (callee, inheriting_impl) in resolved_callees {
+                        if instantiations_set.get(&callee).is_none() {
+                            // Add only if there is no concrete call already added.
+                            let kind = match inheriting_impl {
+                                Some(_) => EdgeKind::DefaultMethod,
+                                None => EdgeKind::GenericVirtual,
+                            };
+                            callees.push((callee, kind, inheriting_impl));
+                            // A default method is added once for every impl that inherits it.
+                            if inheriting_impl.is_none() {
                                 instantiations_set.insert(callee);
                             }
                         }
                     }
-                    Err(_) => {}
-                    // Err(error) => println!("Resoltion failed: {}", error),
                 }
-                if instantiations_set.is_empty() {
-                    // No instantiations found, so we add a static edge to the original callee.
-                    // This can happen if there are no available concretizations of the callee or
-                    // if the function is generic, but not its receiver, thus we cannot treat it
-                    // the call as a virtual dispatch call.
-                    let callee_id = self.add_function_to_callgraph(callgraph, callee);
-                    callgraph.add_static_function_call_edge(caller_id, callee_id);
+                Err(_) => {}
+                // Err(error) => println!("Resoltion failed: {}", error),
+            }
+            if callees.is_empty() {
+                // No instantiations found, so we add a static edge to the original callee.
+                // This can happen if there are no available concretizations of the callee or
+                // if the function is generic, but not its receiver, thus we cannot treat it
+                // the call as a virtual dispatch call.
+                callees.push((*callee, EdgeKind::GenericFallback, None));
+            }
+        } else {
+            callees.push((*callee, EdgeKind::Static, None));
+        }
+        callees
+    }
+    // Resolves a call through a function pointer to the functions and closures with the same
+    // signature that are coerced to function pointers, and a call through a `dyn Fn*` object
+    // also to the closures with the same signature.
+    fn resolve_indirect_call(
+        &self,
+        func_type: &Type,
+        signature: &InternedString,
+    ) -> impl Iterator<Item = &DefPath> {
+        let closures = match self.types.is_dynamic(func_type) {
+            true => self.closure_signatures.get(signature),
+            false => None,
+        };
+        let function_pointer_targets = self.function_pointer_targets.get(signature);
+        function_pointer_targets
+            .into_iter()
+            .flatten()
+            .chain(closures.into_iter().flatten().filter(move |closure| {
+                // A closure that is also coerced to a function pointer is already a target.
+                function_pointer_targets.map_or(true, |targets| !targets.contains(closure))
+            }))
+    }
+    // Computes the ADTs that are not constructed by the functions that are reachable from the
+    // entry points, where virtual calls are resolved only to the impls of the ADTs constructed so
+    // far. Only the ADTs that are defined in the analysed packages and that have a construction
+    // site can be unconstructed; all other ADTs may be constructed in code that is not in the
+    // database.
+    fn compute_unconstructed_adts(&self, aggregates: &AggregatesInfo) -> HashSet<DefPath> {
+        let mut calls: HashMap<DefPath, Vec<(FunctionCall, DefPath)>> = HashMap::new();
+        for (call_id, caller, callee) in self.call_graph.iter() {
+            calls.entry(*caller).or_default().push((*call_id, *callee));
+        }
+        let mut indirect_callees: HashMap<DefPath, Vec<DefPath>> = HashMap::new();
+        for (_, caller, func_type, signature) in &self.indirect_calls {
+            indirect_callees
+                .entry(*caller)
+                .or_default()
+                .extend(self.resolve_indirect_call(func_type, signature));
+        }
+        let mut adt_traits: HashMap<DefPath, HashSet<DefPath>> = HashMap::new();
+        for (trait_def_path, trait_impls) in &self.types.trait_to_impls {
+            for trait_impl in trait_impls {
+                if let Some(adt) = self.types.impl_adt(trait_impl) {
+                    adt_traits.entry(adt).or_default().insert(*trait_def_path);
+                }
+            }
+        }
+        let mut unconstructed_adts: HashSet<DefPath> = aggregates
+            .iter_adts_with_construction_sites()
+            .filter(|adt| self.interning.def_path_to_package(adt).is_some())
+            .cloned()
+            .collect();
+        let mut worklist: Vec<DefPath> = self
+            .functions
+            .iter_def_paths()
+            .filter(|def_path| {
+                self.functions
+                    .is_entry_point(def_path, &self.modules, &self.types)
+            })
+            .cloned()
+            .collect();
+        let mut reachable = HashSet::new();
+        // Calls to trait methods that have to be resolved again when new ADTs that implement the
+        // trait are constructed, indexed by the trait.
+        let mut dynamic_calls: HashMap<DefPath, Vec<(FunctionCall, DefPath)>> = HashMap::new();
+        let mut newly_constructed_adts = Vec::new();
+        loop {
+            while let Some(function) = worklist.pop() {
+                if !reachable.insert(function) {
+                    continue;
+                }
+                for adt in aggregates.iter_constructed_adts(&function) {
+                    if unconstructed_adts.remove(adt) {
+                        newly_constructed_adts.push(*adt);
+                    }
+                }
+                for (call_id, callee) in calls.get(&function).into_iter().flatten() {
+                    if self.virtual_calls.contains(call_id) || self.generic_calls.contains(call_id)
+                    {
+                        if let Some((_, _, trait_def_path)) = self.types.trait_items.get(callee) {
+                            dynamic_calls
+                                .entry(*trait_def_path)
+                                .or_default()
+                                .push((*call_id, *callee));
+                        }
+                    }
+                    let resolved_callees =
+                        self.resolve_call(call_id, callee, Some(&unconstructed_adts));
+                    for (callee, _, _) in resolved_callees {
+                        worklist.push(callee);
+                    }
+                }
+                worklist.extend(indirect_callees.get(&function).into_iter().flatten());
+            }
+            let traits: HashSet<&DefPath> = newly_constructed_adts
+                .drain(..)
+                .filter_map(|adt| adt_traits.get(&adt))
+                .flatten()
+                .collect();
+            for trait_def_path in traits {
+                for (call_id, callee) in dynamic_calls.get(trait_def_path).into_iter().flatten() {
+                    let resolved_callees =
+                        self.resolve_call(call_id, callee, Some(&unconstructed_adts));
+                    for (callee, _, _) in resolved_callees {
+                        if !reachable.contains(&callee) {
+                            worklist.push(callee);
+                        }
+                    }
                 }
-            } else {
-                let callee_id = self.add_function_to_callgraph(callgraph, &callee);
-                callgraph.add_static_function_call_edge(caller_id, callee_id);
+            }
+            if worklist.is_empty() {
+                break;
             }
         }
+        unconstructed_adts
     }
     fn add_macro_calls_to_callgraph(&self, callgraph: &mut CallGraph) {
         for def_path in self.macros.iter_def_paths() {
             self.add_macro_to_callgraph(callgraph, def_path);
         }
-        for (caller_def_path, macro_def_path) in self.macros.iter_macro_calls() {
+        for (caller_def_path, macro_def_path, through) in self.macros.iter_macro_calls() {
             let caller_id = self.add_function_to_callgraph(callgraph, caller_def_path);
             let callee_id = self.add_macro_to_callgraph(callgraph, macro_def_path);
-            callgraph.add_macro_call_edge(caller_id, callee_id);
+            let through_id =
+                through.map(|def_path| self.add_macro_to_callgraph(callgraph, def_path));
+            callgraph.add_macro_call_edge(caller_id, callee_id, through_id);
+        }
+        for (caller_def_path, macro_def_path) in self.macros.iter_nested_macro_calls() {
+            let caller_id = self.add_macro_to_callgraph(callgraph, caller_def_path);
+            let callee_id = self.add_macro_to_callgraph(callgraph, macro_def_path);
+            callgraph.add_nested_macro_call_edge(caller_id, callee_id);
         }
     }
     pub fn run(&'a self) -> CallGraph {
+        let unconstructed_adts = match (self.resolution, &self.aggregates) {
+            (VirtualCallResolution::Rta, Some(aggregates)) => {
+                Some(self.compute_unconstructed_adts(aggregates))
+            }
+            _ => None,
+        };
         let mut callgraph = CallGraph::new();
-        self.add_function_calls_to_callgraph(&mut callgraph);
+        self.add_function_calls_to_callgraph(&mut callgraph, unconstructed_adts.as_ref());
         self.add_macro_calls_to_callgraph(&mut callgraph);
         callgraph
     }
+    // Resolves a call to a trait method to the corresponding items of the trait impls. An impl
+    // that does not override a provided method resolves to the default body of the trait, which
+    // is returned together with the impl. If `unconstructed_adts` is given, impls for ADTs that
+    // are in the set are skipped, while impls for other types, e.g., references or type
+    // parameters, are kept. If the receiver is a `dyn` object of a subtrait of the method's
+    // trait, only the impls for the types that also implement the subtrait are considered,
+    // unless the subtrait or the impl is a blanket impl for a type parameter.
     fn resolve_virtual_call(
-        &'a self,
+        &self,
         function_def_path: &DefPath,
-    ) -> Result<Vec<DefPath>, Box<dyn std::error::Error>> {
-        let (function_name, _, trait_def_path) = self
+        receiver_trait_def_path: Option<&DefPath>,
+        unconstructed_adts: Option<&HashSet<DefPath>>,
+    ) -> Result<Vec<(DefPath, Option<DefPath>)>, Box<dyn std::error::Error>> {
+        let (function_name, defaultness, trait_def_path) = self
             .types
             .trait_items
             .get(function_def_path)
@@ -187,29 +469,96 @@ impl<'a> CallGraphAnalysis<'a> {
             .trait_to_impls
             .get(trait_def_path)
             .ok_or("Trait is not registered for impls.")?;
+        let receiver_types = receiver_trait_def_path
+            .filter(|receiver| self.types.is_supertrait(receiver, trait_def_path))
+            .map(|receiver| self.types.implementing_types(receiver))
+            // A blanket impl of the subtrait may be an impl for any type.
+            .filter(|(_, types)| !types.iter().any(|typ| self.types.is_type_param(typ)));
         let mut is_implemented_by_all = true;
         let mut resolved_functions = Vec::new();
         for trait_impl in trait_impls {
+            if let Some((adts, types)) = &receiver_types {
+                let (_, self_type) = self.types.get_impl_types(trait_impl);
+                let implements_receiver = match self.types.impl_adt(trait_impl) {
+                    Some(adt) => adts.contains(&adt),
+                    None => self.types.is_type_param(&self_type) || types.contains(&self_type),
+                };
+                if !implements_receiver {
+                    continue;
+                }
+            }
+            if let (Some(unconstructed_adts), Some(adt)) =
+                (unconstructed_adts, self.types.impl_adt(trait_impl))
+            {
+                if unconstructed_adts.contains(&adt) {
+                    continue;
+                }
+            }
             if let Some(items) = self.types.trait_impl_to_items.get(trait_impl) {
                 if let Some(item) = items.get(function_name) {
-                    resolved_functions.push(*item);
+                    resolved_functions.push((*item, None));
                     continue;
                 }
             }
-            is_implemented_by_all = false;
+            match defaultness {
+                // The impl inherits the default body of the trait method.
+                // TODO: Handle impl specialization implemented in the following pull request
+                // https://github.com/rust-lang/rfcs/pull/1210
+                // Specialization is available only in the nightly rustc.
+                // Package im-rc 13.0.0, the specialization feature in files
+                //   * https://docs.rs/crate/im-rc/13.0.0/source/src/ord/map.rs
+                //   * https://docs.rs/crate/im-rc/13.0.0/source/src/ord/set.rs
+                Defaultness::DefaultWithValue => {
+                    resolved_functions.push((*function_def_path, Some(*trait_impl)))
+                }
+                // A required method can be missing only if it is inherited from a more general
+                // impl. The method has no body, so it is not a callee.
+                Defaultness::DefaultNoValue => {}
+                _ => is_implemented_by_all = false,
+            }
         }
         if !is_implemented_by_all {
-            // TODO: Handle impl specialization implemented in the following pull request
-            // https://github.com/rust-lang/rfcs/pull/1210
-            // Specialization is available only in the nightly rustc.
-            // Package im-rc 13.0.0, the specialization feature in files
-            //   * https://docs.rs/crate/im-rc/13.0.0/source/src/ord/map.rs
-            //   * https://docs.rs/crate/im-rc/13.0.0/source/src/ord/set.rs
-            resolved_functions.push(*function_def_path);
+            // The defaultness of the trait method is unknown.
+            resolved_functions.push((*function_def_path, None));
         }
         Ok(resolved_functions)
     }
     pub fn types(&self) -> TypeHierarchy {
         TypeHierarchy::new(&self.types, &self.interning)
     }
+    pub fn dead_code(&self, callgraph: &CallGraph, filter: EdgeFilter) -> DeadCodeReport {
+        DeadCodeReport::new(
+            callgraph,
+            &self.functions,
+            &self.modules,
+            &self.types,
+            &self.interning,
+            filter,
+        )
+    }
+    pub fn ffi(&self, callgraph: &CallGraph, filter: EdgeFilter) -> FfiReport {
+        FfiReport::new(callgraph, &self.functions, &self.modules, filter)
+    }
+    pub fn panic_reachability(
+        &self,
+        callgraph: &CallGraph,
+        filter: EdgeFilter,
+        all_functions: bool,
+        exclude_overflow: bool,
+    ) -> PanicReachabilityReport {
+        PanicReachabilityReport::new(
+            callgraph,
+            &self.asserts,
+            filter,
+            all_functions,
+            exclude_overflow,
+        )
+    }
+    pub fn unsafe_reachability(
+        &self,
+        callgraph: &CallGraph,
+        filter: EdgeFilter,
+    ) -> UnsafeReachabilityReport {
+        UnsafeReachabilityReport::new(callgraph, &self.unsafe_code, filter)
+    }
 }
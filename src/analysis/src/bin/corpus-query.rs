@@ -0,0 +1,67 @@
+use analysis::shell::QueryShell;
+use corpus_database::tables::Tables;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "corpus-query",
+    about = "Interactive queries over a database that is loaded once."
+)]
+struct CMDArgs {
+    #[structopt(
+        parse(from_os_str),
+        default_value = "../../database",
+        long = "database",
+        help = "The directory in which the database is stored."
+    )]
+    database_root: PathBuf,
+    #[structopt(
+        parse(from_os_str),
+        long = "script",
+        help = "A file with one command per line that is executed instead of reading the standard input."
+    )]
+    script: Option<PathBuf>,
+}
+
+fn main() {
+    let args = CMDArgs::from_args();
+    let database_root = Path::new(&args.database_root);
+    let tables = Tables::load_multifile(database_root).unwrap();
+    let shell = QueryShell::new(&tables);
+    let stdout = io::stdout();
+    let mut writer = stdout.lock();
+    match &args.script {
+        Some(path) => {
+            let file =
+                File::open(path).unwrap_or_else(|e| panic!("Unable to open {:?}: {}", path, e));
+            for line in BufReader::new(file).lines() {
+                let line = line.unwrap();
+                if !line.trim().is_empty() && !line.trim().starts_with('#') {
+                    writeln!(writer, "> {}", line.trim()).unwrap();
+                }
+                if !shell.execute(&line, &mut writer).unwrap() {
+                    break;
+                }
+            }
+        }
+        None => {
+            eprintln!("Loaded the database. Type `help` for the list of commands.");
+            let stdin = io::stdin();
+            let mut lines = stdin.lock().lines();
+            loop {
+                write!(writer, "> ").unwrap();
+                writer.flush().unwrap();
+                let line = match lines.next() {
+                    Some(line) => line.unwrap(),
+                    None => break,
+                };
+                if !shell.execute(&line, &mut writer).unwrap() {
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use analysis::rules::{DerivedRelations, RuleProgram};
+use corpus_database::tables::Tables;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "corpus-rules",
+    about = "Evaluates Datalog rules over a database and writes the derived relations as CSV."
+)]
+struct CMDArgs {
+    #[structopt(
+        parse(from_os_str),
+        default_value = "../../database",
+        long = "database",
+        help = "The directory in which the database is stored."
+    )]
+    database_root: PathBuf,
+    #[structopt(parse(from_os_str), help = "The file with the rules.")]
+    rules: PathBuf,
+    #[structopt(
+        parse(from_os_str),
+        long = "output",
+        help = "The directory into which a CSV file per output relation is written instead of the standard output."
+    )]
+    output: Option<PathBuf>,
+}
+
+fn main() {
+    let args = CMDArgs::from_args();
+    let rules = std::fs::read_to_string(&args.rules)
+        .unwrap_or_else(|e| panic!("Unable to read {:?}: {}", args.rules, e));
+    let program: RuleProgram = rules
+        .parse()
+        .unwrap_or_else(|e| panic!("Invalid rules in {:?}: {}", args.rules, e));
+    let database_root = Path::new(&args.database_root);
+    let tables = Tables::load_multifile(database_root).unwrap();
+    let relations = DerivedRelations::new(&tables, &program)
+        .unwrap_or_else(|e| panic!("Invalid rules in {:?}: {}", args.rules, e));
+    match &args.output {
+        Some(directory) => {
+            std::fs::create_dir_all(directory).unwrap();
+            for output in relations.outputs() {
+                let path = directory.join(format!("{}.csv", output));
+                let mut file = File::create(&path)
+                    .unwrap_or_else(|e| panic!("Unable to create {:?}: {}", path, e));
+                relations.write_csv(output, &mut file).unwrap();
+            }
+        }
+        None => {
+            let stdout = io::stdout();
+            let mut writer = stdout.lock();
+            for output in relations.outputs() {
+                writeln!(writer, "# {}", output).unwrap();
+                relations.write_csv(output, &mut writer).unwrap();
+            }
+        }
+    }
+}
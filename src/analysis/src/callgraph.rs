@@ -1,10 +1,10 @@
-use corpus_database::types::DefPath;
+use corpus_database::types::{DefPath, Unsafety};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub type NodeId = usize;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Node {
     pub id: NodeId,
     pub package_name: Option<String>,
@@ -14,20 +14,140 @@ pub struct Node {
     pub is_externally_visible: bool,
     pub num_lines: i32,
     pub source_location: Option<String>,
+    // `None` for macros and for functions that are not defined in the database.
+    #[serde(flatten)]
+    pub details: Option<FunctionDetails>,
+}
+
+/// The kind of a function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FunctionKind {
+    /// A free function.
+    Fn,
+    /// An associated function of an impl.
+    Method,
+    /// An associated function of a trait, i.e., a provided trait method.
+    TraitMethod,
+    Closure,
+    /// A function declared in an `extern` block.
+    Foreign,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FunctionDetails {
+    pub kind: FunctionKind,
+    pub unsafety: Unsafety,
+    pub abi: String,
+    // Whether the function uses unsafe operations directly in its body. `None` if unknown.
+    pub uses_unsafe: Option<bool>,
+    pub is_generic: bool,
+    pub parameter_types: Vec<String>,
+    pub return_type: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CallSite {
+    // The location in the source code. For call sites that are created by a macro expansion,
+    // it is the location of the outermost macro invocation.
+    pub location: String,
+    pub is_macro_expanded: bool,
+}
+
+/// How a call edge was resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EdgeKind {
+    /// A statically dispatched call to a non-generic function.
+    Static,
+    /// A call to a generic function resolved to one of its monomorphized instances.
+    Instance,
+    /// A virtual call resolved to the method of an impl by CHA or RTA.
+    Virtual,
+    /// A virtual call resolved to a default trait method that an impl inherits.
+    DefaultMethod,
+    /// A call to a generic function that is over-approximated as a virtual call.
+    GenericVirtual,
+    /// A call to a generic function for which no instance is found, so the edge points to the
+    /// generic function itself.
+    GenericFallback,
+    /// A call through a function pointer or a `dyn Fn*` object.
+    Indirect,
+}
+
+impl EdgeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeKind::Static => "static",
+            EdgeKind::Instance => "instance",
+            EdgeKind::Virtual => "virtual",
+            EdgeKind::DefaultMethod => "default-method",
+            EdgeKind::GenericVirtual => "generic-virtual",
+            EdgeKind::GenericFallback => "generic-fallback",
+            EdgeKind::Indirect => "indirect",
+        }
+    }
+    /// Returns true if the call is statically dispatched.
+    pub fn is_static(&self) -> bool {
+        match self {
+            EdgeKind::Static | EdgeKind::Instance | EdgeKind::GenericFallback => true,
+            _ => false,
+        }
+    }
+    /// Returns true if the edge comes from over-approximating a call to a generic function or
+    /// an indirect call, which is resolved by the signature alone, so the call may not happen.
+    pub fn is_speculative(&self) -> bool {
+        match self {
+            EdgeKind::GenericVirtual | EdgeKind::GenericFallback | EdgeKind::Indirect => true,
+            _ => false,
+        }
+    }
+}
+
+/// A call-graph edge. Parallel calls of the same kind are merged into one edge.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Edge {
+    pub caller: NodeId,
+    pub callee: NodeId,
+    pub kind: EdgeKind,
+    // The number of calls that are merged into the edge.
+    pub num_calls: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub call_sites: Vec<CallSite>,
+    // For edges to default trait methods, the implementing type of each impl through which the
+    // default method is inherited.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub implementing_types: Vec<String>,
+}
+
+/// An invocation of a macro in the body of a function.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MacroCall {
+    pub caller: NodeId,
+    pub callee: NodeId,
+    // The macro whose expansion contains the invocation, if the function does not invoke the
+    // callee macro directly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub through: Option<NodeId>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct CallGraph {
     // Call-graph function nodes
-    functions: Vec<Node>,
+    pub(crate) functions: Vec<Node>,
     // Call-graph function nodes
-    macros: Vec<Node>,
+    pub(crate) macros: Vec<Node>,
     // Call-graph edges, i.e., caller function calls callee function.
-    // The boolean value indicates if the call is statically dispatched.
-    function_calls: Vec<(NodeId, NodeId, bool)>,
-    macro_calls: Vec<(NodeId, NodeId)>,
+    pub(crate) function_calls: Vec<Edge>,
+    // Macro invocations, i.e., caller function invokes callee macro.
+    pub(crate) macro_calls: Vec<MacroCall>,
+    // Macro invocations within macro expansions, i.e., the expansion of the caller macro
+    // invokes the callee macro.
+    pub(crate) nested_macro_calls: Vec<(NodeId, NodeId)>,
     #[serde(skip)]
-    node_registry: HashMap<DefPath, usize>,
+    pub(crate) node_registry: HashMap<DefPath, usize>,
+    #[serde(skip)]
+    pub(crate) edge_registry: HashMap<(NodeId, NodeId, EdgeKind), usize>,
 }
 
 impl CallGraph {
@@ -37,7 +157,9 @@ impl CallGraph {
             macros: Vec::new(),
             function_calls: Vec::new(),
             macro_calls: Vec::new(),
+            nested_macro_calls: Vec::new(),
             node_registry: HashMap::new(),
+            edge_registry: HashMap::new(),
         }
     }
     pub fn add_node(
@@ -50,6 +172,7 @@ impl CallGraph {
         num_lines: i32,
         is_macro: bool,
         source_location: Option<String>,
+        details: Option<FunctionDetails>,
     ) -> NodeId {
         let mut package_name = None;
         let mut package_version = None;
@@ -72,17 +195,71 @@ impl CallGraph {
             is_externally_visible,
             num_lines,
             source_location,
+            details,
         });
         id
     }
-    pub fn add_static_function_call_edge(&mut self, caller_id: NodeId, callee_id: NodeId) {
-        self.function_calls.push((caller_id, callee_id, true));
+    /// Adds a call from `caller_id` to `callee_id`. If an edge of the same kind exists, the
+    /// call is merged into it.
+    pub fn add_function_call_edge(
+        &mut self,
+        caller_id: NodeId,
+        callee_id: NodeId,
+        kind: EdgeKind,
+        call_site: Option<CallSite>,
+    ) {
+        let edge = self.edge_mut(caller_id, callee_id, kind);
+        edge.num_calls += 1;
+        if let Some(call_site) = call_site {
+            edge.call_sites.push(call_site);
+        }
+    }
+    /// Records that the default method of the edge is inherited by the impl of
+    /// `implementing_type`.
+    pub fn add_implementing_type(
+        &mut self,
+        caller_id: NodeId,
+        callee_id: NodeId,
+        kind: EdgeKind,
+        implementing_type: String,
+    ) {
+        let edge = self.edge_mut(caller_id, callee_id, kind);
+        if !edge.implementing_types.contains(&implementing_type) {
+            edge.implementing_types.push(implementing_type);
+        }
     }
-    pub fn add_virtual_function_call_edge(&mut self, caller_id: NodeId, callee_id: NodeId) {
-        self.function_calls.push((caller_id, callee_id, false));
+    fn edge_mut(&mut self, caller_id: NodeId, callee_id: NodeId, kind: EdgeKind) -> &mut Edge {
+        let function_calls = &mut self.function_calls;
+        let index = *self
+            .edge_registry
+            .entry((caller_id, callee_id, kind))
+            .or_insert_with(|| {
+                function_calls.push(Edge {
+                    caller: caller_id,
+                    callee: callee_id,
+                    kind,
+                    num_calls: 0,
+                    call_sites: Vec::new(),
+                    implementing_types: Vec::new(),
+                });
+                function_calls.len() - 1
+            });
+        &mut self.function_calls[index]
+    }
+    pub fn add_macro_call_edge(
+        &mut self,
+        caller_id: NodeId,
+        callee_id: NodeId,
+        through_id: Option<NodeId>,
+    ) {
+        self.macro_calls.push(MacroCall {
+            caller: caller_id,
+            callee: callee_id,
+            through: through_id,
+        });
     }
-    pub fn add_macro_call_edge(&mut self, caller_id: NodeId, callee_id: NodeId) {
-        self.macro_calls.push((caller_id, callee_id));
+    pub fn add_nested_macro_call_edge(&mut self, caller_id: NodeId, callee_id: NodeId) {
+        self.nested_macro_calls.push((caller_id, callee_id));
     }
     pub fn get_node_by_def_path(&self, def_path: &DefPath) -> Option<&NodeId> {
         self.node_registry.get(def_path)
@@ -0,0 +1,108 @@
+use crate::callgraph::{CallGraph, NodeId};
+use crate::info::{FunctionsInfo, InterningInfo, ModulesInfo, TypeInfo};
+use crate::query::{Direction, EdgeFilter};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+#[derive(Serialize)]
+pub struct DeadFunction {
+    id: NodeId,
+    relative_def_id: String,
+    num_lines: i32,
+    source_location: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PackageDeadCode {
+    package_name: Option<String>,
+    package_version: Option<String>,
+    num_functions: usize,
+    num_lines: i64,
+    num_entry_points: usize,
+    num_dead_functions: usize,
+    num_dead_lines: i64,
+    dead_functions: Vec<DeadFunction>,
+}
+
+/// Functions that are unreachable from any entry point, grouped by package.
+///
+/// Entry points are externally visible functions, `main` functions of binary crates, trait
+/// impl items, and functions with a foreign ABI. Only the call edges accepted by the filter are
+/// followed, so that the speculative edges, which may reach functions that are never called,
+/// can be excluded.
+#[derive(Serialize)]
+pub struct DeadCodeReport {
+    packages: Vec<PackageDeadCode>,
+}
+
+impl DeadCodeReport {
+    pub(crate) fn new(
+        callgraph: &CallGraph,
+        functions: &FunctionsInfo,
+        modules: &ModulesInfo,
+        types: &TypeInfo,
+        interning: &InterningInfo,
+        filter: EdgeFilter,
+    ) -> Self {
+        let mut entry_points = Vec::new();
+        for def_path in functions.iter_def_paths() {
+            if functions.is_entry_point(def_path, modules, types) {
+                if let Some(node_id) = callgraph.get_node_by_def_path(def_path) {
+                    entry_points.push(*node_id);
+                }
+            }
+        }
+        let reachable = callgraph.reachable_nodes(&entry_points, Direction::Forward, None, filter);
+        let mut packages: HashMap<Option<(String, String)>, PackageDeadCode> = HashMap::new();
+        for def_path in functions.iter_def_paths() {
+            let node_id = match callgraph.get_node_by_def_path(def_path) {
+                Some(node_id) => *node_id,
+                None => continue,
+            };
+            let package_info = interning.def_path_to_package(def_path);
+            let package = packages
+                .entry(package_info.clone())
+                .or_insert_with(|| PackageDeadCode {
+                    package_name: package_info.as_ref().map(|(name, _)| name.clone()),
+                    package_version: package_info.as_ref().map(|(_, version)| version.clone()),
+                    num_functions: 0,
+                    num_lines: 0,
+                    num_entry_points: 0,
+                    num_dead_functions: 0,
+                    num_dead_lines: 0,
+                    dead_functions: Vec::new(),
+                });
+            let num_lines = functions.functions_num_lines(def_path);
+            package.num_functions += 1;
+            package.num_lines += i64::from(num_lines);
+            if functions.is_entry_point(def_path, modules, types) {
+                package.num_entry_points += 1;
+            }
+            if !reachable.contains(&node_id) {
+                package.num_dead_functions += 1;
+                package.num_dead_lines += i64::from(num_lines);
+                package.dead_functions.push(DeadFunction {
+                    id: node_id,
+                    relative_def_id: interning.def_path_to_string(def_path),
+                    num_lines,
+                    source_location: functions.functions_source_location(def_path),
+                });
+            }
+        }
+        let mut packages: Vec<_> = packages.into_iter().map(|(_, package)| package).collect();
+        packages.sort_by(|a, b| {
+            (&a.package_name, &a.package_version).cmp(&(&b.package_name, &b.package_version))
+        });
+        for package in &mut packages {
+            package
+                .dead_functions
+                .sort_by(|a, b| a.relative_def_id.cmp(&b.relative_def_id));
+        }
+        Self { packages }
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)
+    }
+}
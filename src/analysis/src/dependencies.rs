@@ -0,0 +1,279 @@
+use crate::callgraph::{CallGraph, Node, NodeId};
+use crate::export::{escape_dot, escape_xml, write_json_line, OutputFormat};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// The granularity at which the call-graph is collapsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DependencyLevel {
+    Package,
+    Crate,
+}
+
+impl FromStr for DependencyLevel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "package" => Ok(DependencyLevel::Package),
+            "crate" => Ok(DependencyLevel::Crate),
+            _ => Err(format!("Unknown dependency level: {}", s)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct DependencyNode {
+    id: usize,
+    package_name: Option<String>,
+    package_version: Option<String>,
+    // `None` for package level graphs.
+    crate_name: Option<String>,
+    num_functions: usize,
+    num_externally_visible_functions: usize,
+}
+
+#[derive(Serialize)]
+pub struct DependencyEdge {
+    source: usize,
+    target: usize,
+    // The number of function calls from the source to the target.
+    num_calls: usize,
+    // The number of distinct functions of the target that are called by the source.
+    num_callees: usize,
+    num_static_calls: usize,
+    num_virtual_calls: usize,
+}
+
+/// A line of the JSON Lines output.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record<'a> {
+    Node(&'a DependencyNode),
+    Edge(&'a DependencyEdge),
+}
+
+/// The call-graph collapsed into a weighted graph of packages (or crates), where an edge
+/// summarizes the calls from the functions of one package to the functions of another.
+/// Calls within the same package are not included.
+#[derive(Serialize)]
+pub struct DependencyGraph {
+    nodes: Vec<DependencyNode>,
+    edges: Vec<DependencyEdge>,
+}
+
+impl DependencyGraph {
+    pub fn new(callgraph: &CallGraph, level: DependencyLevel) -> Self {
+        let key = |node: &Node| {
+            let crate_name = match level {
+                DependencyLevel::Package => None,
+                DependencyLevel::Crate => Some(node.crate_name.clone()),
+            };
+            (
+                node.package_name.clone(),
+                node.package_version.clone(),
+                crate_name,
+            )
+        };
+        let mut keys = BTreeMap::new();
+        for node in &callgraph.functions {
+            keys.entry(key(node)).or_insert(0);
+        }
+        // Ids follow the order of the keys.
+        for (index, id) in keys.values_mut().enumerate() {
+            *id = index;
+        }
+        let mut nodes: Vec<_> = keys
+            .keys()
+            .enumerate()
+            .map(
+                |(id, (package_name, package_version, crate_name))| DependencyNode {
+                    id,
+                    package_name: package_name.clone(),
+                    package_version: package_version.clone(),
+                    crate_name: crate_name.clone(),
+                    num_functions: 0,
+                    num_externally_visible_functions: 0,
+                },
+            )
+            .collect();
+        let mut node_to_group = HashMap::new();
+        for node in &callgraph.functions {
+            let group = keys[&key(node)];
+            node_to_group.insert(node.id, group);
+            nodes[group].num_functions += 1;
+            if node.is_externally_visible {
+                nodes[group].num_externally_visible_functions += 1;
+            }
+        }
+        let mut edges: BTreeMap<(usize, usize), DependencyEdge> = BTreeMap::new();
+        let mut callees: HashMap<(usize, usize), HashSet<NodeId>> = HashMap::new();
+        for call in &callgraph.function_calls {
+            let source = node_to_group[&call.caller];
+            let target = node_to_group[&call.callee];
+            if source == target {
+                continue;
+            }
+            let edge = edges
+                .entry((source, target))
+                .or_insert_with(|| DependencyEdge {
+                    source,
+                    target,
+                    num_calls: 0,
+                    num_callees: 0,
+                    num_static_calls: 0,
+                    num_virtual_calls: 0,
+                });
+            edge.num_calls += call.num_calls;
+            match call.kind.is_static() {
+                true => edge.num_static_calls += call.num_calls,
+                false => edge.num_virtual_calls += call.num_calls,
+            }
+            callees
+                .entry((source, target))
+                .or_default()
+                .insert(call.callee);
+        }
+        for (group_pair, edge) in &mut edges {
+            edge.num_callees = callees[group_pair].len();
+        }
+        Self {
+            nodes,
+            edges: edges.into_iter().map(|(_, edge)| edge).collect(),
+        }
+    }
+    /// Writes the dependency graph to `writer` in the given `format`.
+    pub fn write<W: Write>(&self, format: OutputFormat, writer: &mut W) -> io::Result<()> {
+        match format {
+            OutputFormat::Json => {
+                serde_json::to_writer_pretty(&mut *writer, self)?;
+                writeln!(writer)
+            }
+            OutputFormat::JsonLines => {
+                for node in &self.nodes {
+                    write_json_line(writer, &Record::Node(node))?;
+                }
+                for edge in &self.edges {
+                    write_json_line(writer, &Record::Edge(edge))?;
+                }
+                Ok(())
+            }
+            OutputFormat::Dot => self.write_dot(writer),
+            OutputFormat::GraphMl => self.write_graphml(writer),
+        }
+    }
+    fn write_dot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "digraph dependencies {{")?;
+        for node in &self.nodes {
+            writeln!(
+                writer,
+                "    n{} [label=\"{}\", num_functions={}, num_externally_visible_functions={}];",
+                node.id,
+                escape_dot(&node.label()),
+                node.num_functions,
+                node.num_externally_visible_functions,
+            )?;
+        }
+        for edge in &self.edges {
+            writeln!(
+                writer,
+                "    n{} -> n{} [label=\"{}\", weight={}, num_calls={}, num_callees={}, \
+                 num_static_calls={}, num_virtual_calls={}];",
+                edge.source,
+                edge.target,
+                edge.num_calls,
+                edge.num_calls,
+                edge.num_calls,
+                edge.num_callees,
+                edge.num_static_calls,
+                edge.num_virtual_calls,
+            )?;
+        }
+        writeln!(writer, "}}")
+    }
+    fn write_graphml<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            writer,
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+        )?;
+        // Attribute declarations: (id, domain, name, type).
+        let keys = [
+            ("d0", "node", "label", "string"),
+            ("d1", "node", "num_functions", "int"),
+            ("d2", "node", "num_externally_visible_functions", "int"),
+            ("d3", "edge", "num_calls", "int"),
+            ("d4", "edge", "num_callees", "int"),
+            ("d5", "edge", "num_static_calls", "int"),
+            ("d6", "edge", "num_virtual_calls", "int"),
+        ];
+        for (id, domain, name, typ) in keys.iter() {
+            writeln!(
+                writer,
+                "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                id, domain, name, typ
+            )?;
+        }
+        writeln!(
+            writer,
+            "  <graph id=\"dependencies\" edgedefault=\"directed\">"
+        )?;
+        for node in &self.nodes {
+            writeln!(writer, "    <node id=\"n{}\">", node.id)?;
+            writeln!(
+                writer,
+                "      <data key=\"d0\">{}</data>",
+                escape_xml(&node.label())
+            )?;
+            writeln!(
+                writer,
+                "      <data key=\"d1\">{}</data>",
+                node.num_functions
+            )?;
+            writeln!(
+                writer,
+                "      <data key=\"d2\">{}</data>",
+                node.num_externally_visible_functions
+            )?;
+            writeln!(writer, "    </node>")?;
+        }
+        for edge in &self.edges {
+            writeln!(
+                writer,
+                "    <edge source=\"n{}\" target=\"n{}\">",
+                edge.source, edge.target
+            )?;
+            writeln!(writer, "      <data key=\"d3\">{}</data>", edge.num_calls)?;
+            writeln!(writer, "      <data key=\"d4\">{}</data>", edge.num_callees)?;
+            writeln!(
+                writer,
+                "      <data key=\"d5\">{}</data>",
+                edge.num_static_calls
+            )?;
+            writeln!(
+                writer,
+                "      <data key=\"d6\">{}</data>",
+                edge.num_virtual_calls
+            )?;
+            writeln!(writer, "    </edge>")?;
+        }
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")
+    }
+}
+
+impl DependencyNode {
+    // Returns a label of the form "package version" or "package version crate".
+    fn label(&self) -> String {
+        let mut label = match (&self.package_name, &self.package_version) {
+            (Some(name), Some(version)) => format!("{} {}", name, version),
+            _ => String::from("unknown"),
+        };
+        if let Some(crate_name) = &self.crate_name {
+            label.push(' ');
+            label.push_str(crate_name);
+        }
+        label
+    }
+}
@@ -0,0 +1,219 @@
+use crate::callgraph::{CallGraph, Node, NodeId};
+use crate::query::strip_disambiguators;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{self, Write};
+
+#[derive(Serialize)]
+pub struct CalleesChange {
+    function: String,
+    added_callees: Vec<String>,
+    removed_callees: Vec<String>,
+}
+
+/// The differences between the call-graphs of two versions of the same package.
+///
+/// Functions are matched by their relative def path without the crate disambiguator, which
+/// changes between versions. The `DefPathHash` of a function includes the crate disambiguator
+/// too, so it cannot be used to match functions across versions.
+#[derive(Serialize)]
+pub struct CallGraphDiff {
+    package_name: String,
+    old_version: String,
+    new_version: String,
+    added_functions: Vec<String>,
+    removed_functions: Vec<String>,
+    added_calls: Vec<(String, String)>,
+    removed_calls: Vec<(String, String)>,
+    changed_callees: Vec<CalleesChange>,
+}
+
+impl CallGraphDiff {
+    /// Compares the functions of `package_name` in `old_version` and `new_version`. If
+    /// `ignore_disambiguators` is true, all disambiguators are removed from the def paths, not
+    /// only the crate ones. Returns an error if either version has no functions in the
+    /// call-graph.
+    pub fn new(
+        callgraph: &CallGraph,
+        package_name: &str,
+        old_version: &str,
+        new_version: &str,
+        ignore_disambiguators: bool,
+    ) -> Result<Self, String> {
+        // Functions with the same key are the same function in both versions.
+        let keys: HashMap<NodeId, String> = callgraph
+            .functions
+            .iter()
+            .map(|node| {
+                let key = match ignore_disambiguators {
+                    true => strip_disambiguators(&node.relative_def_id),
+                    false => strip_crate_disambiguator(&node.relative_def_id),
+                };
+                (node.id, key)
+            })
+            .collect();
+        let old_callees = Self::package_callees(callgraph, &keys, package_name, old_version);
+        let new_callees = Self::package_callees(callgraph, &keys, package_name, new_version);
+        for (version, callees) in &[(old_version, &old_callees), (new_version, &new_callees)] {
+            if callees.is_empty() {
+                return Err(format!(
+                    "The call-graph has no functions of {} {}.",
+                    package_name, version
+                ));
+            }
+        }
+        let mut diff = Self {
+            package_name: package_name.to_string(),
+            old_version: old_version.to_string(),
+            new_version: new_version.to_string(),
+            added_functions: Vec::new(),
+            removed_functions: Vec::new(),
+            added_calls: Vec::new(),
+            removed_calls: Vec::new(),
+            changed_callees: Vec::new(),
+        };
+        for (function, callees) in &new_callees {
+            match old_callees.get(function) {
+                None => {
+                    diff.added_functions.push(function.clone());
+                    for callee in callees {
+                        diff.added_calls.push((function.clone(), callee.clone()));
+                    }
+                }
+                Some(old) => {
+                    let added_callees: Vec<_> = callees.difference(old).cloned().collect();
+                    let removed_callees: Vec<_> = old.difference(callees).cloned().collect();
+                    for callee in &added_callees {
+                        diff.added_calls.push((function.clone(), callee.clone()));
+                    }
+                    for callee in &removed_callees {
+                        diff.removed_calls.push((function.clone(), callee.clone()));
+                    }
+                    if !added_callees.is_empty() || !removed_callees.is_empty() {
+                        diff.changed_callees.push(CalleesChange {
+                            function: function.clone(),
+                            added_callees,
+                            removed_callees,
+                        });
+                    }
+                }
+            }
+        }
+        for (function, callees) in &old_callees {
+            if !new_callees.contains_key(function) {
+                diff.removed_functions.push(function.clone());
+                for callee in callees {
+                    diff.removed_calls.push((function.clone(), callee.clone()));
+                }
+            }
+        }
+        diff.added_functions.sort();
+        diff.removed_functions.sort();
+        diff.added_calls.sort();
+        diff.removed_calls.sort();
+        Ok(diff)
+    }
+    // Returns the mapping from the functions of the given package version to their callees.
+    fn package_callees(
+        callgraph: &CallGraph,
+        keys: &HashMap<NodeId, String>,
+        package_name: &str,
+        package_version: &str,
+    ) -> BTreeMap<String, BTreeSet<String>> {
+        let is_in_package = |node: &Node| {
+            node.package_name.as_ref().map(String::as_str) == Some(package_name)
+                && node.package_version.as_ref().map(String::as_str) == Some(package_version)
+        };
+        let mut callees: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let mut package_nodes = HashSet::new();
+        for node in callgraph
+            .functions
+            .iter()
+            .filter(|node| is_in_package(node))
+        {
+            callees.entry(keys[&node.id].clone()).or_default();
+            package_nodes.insert(node.id);
+        }
+        for edge in &callgraph.function_calls {
+            // Functions of other versions of the package have the same keys.
+            if package_nodes.contains(&edge.caller) {
+                callees
+                    .get_mut(&keys[&edge.caller])
+                    .unwrap()
+                    .insert(keys[&edge.callee].clone());
+            }
+        }
+        callees
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)
+    }
+}
+
+/// Removes the disambiguator of the crate, i.e., the first segment, of a relative def path.
+fn strip_crate_disambiguator(def_path: &str) -> String {
+    match (def_path.find('['), def_path.find("::")) {
+        (Some(start), Some(separator)) if start < separator => {
+            let end = def_path[start..]
+                .find(']')
+                .map_or(separator, |end| start + end + 1);
+            format!("{}{}", &def_path[..start], &def_path[end..])
+        }
+        _ => def_path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callgraph::EdgeKind;
+    use corpus_database::types::DefPath;
+
+    fn add_function(callgraph: &mut CallGraph, version: &str, relative_def_id: &str) -> NodeId {
+        let def_path = DefPath::from(callgraph.functions.len());
+        let package_info = Some((String::from("pkg"), String::from(version)));
+        let crate_name = String::from("pkg");
+        let relative_def_id = relative_def_id.to_string();
+        callgraph.add_node(
+            &def_path,
+            package_info,
+            crate_name,
+            relative_def_id,
+            true,
+            1,
+            false,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn different_crate_hashes() {
+        let mut callgraph = CallGraph::new();
+        let old_a = add_function(&mut callgraph, "1.0.0", "pkg[1a2b]::a");
+        let old_b = add_function(&mut callgraph, "1.0.0", "pkg[1a2b]::b");
+        let new_a = add_function(&mut callgraph, "1.1.0", "pkg[3c4d]::a");
+        let new_c = add_function(&mut callgraph, "1.1.0", "pkg[3c4d]::c");
+        callgraph.add_function_call_edge(old_a, old_b, EdgeKind::Static, None);
+        callgraph.add_function_call_edge(new_a, new_c, EdgeKind::Static, None);
+        let diff = CallGraphDiff::new(&callgraph, "pkg", "1.0.0", "1.1.0", false).unwrap();
+        assert_eq!(diff.added_functions, ["pkg::c"]);
+        assert_eq!(diff.removed_functions, ["pkg::b"]);
+        let call = |caller: &str, callee: &str| (caller.to_string(), callee.to_string());
+        assert_eq!(diff.added_calls, [call("pkg::a", "pkg::c")]);
+        assert_eq!(diff.removed_calls, [call("pkg::a", "pkg::b")]);
+        assert_eq!(diff.changed_callees.len(), 1);
+        assert_eq!(diff.changed_callees[0].function, "pkg::a");
+    }
+
+    #[test]
+    fn missing_version() {
+        let mut callgraph = CallGraph::new();
+        add_function(&mut callgraph, "1.0.0", "pkg[1a2b]::a");
+        let error = CallGraphDiff::new(&callgraph, "pkg", "1.0.0", "2.0.0", false)
+            .err()
+            .unwrap();
+        assert_eq!(error, "The call-graph has no functions of pkg 2.0.0.");
+    }
+}
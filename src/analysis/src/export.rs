@@ -0,0 +1,293 @@
+use crate::callgraph::{CallGraph, Edge, EdgeKind, MacroCall, Node, NodeId};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// The format in which the call-graph is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    /// One JSON object per line for each node and edge.
+    JsonLines,
+    Dot,
+    GraphMl,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::JsonLines),
+            "dot" => Ok(OutputFormat::Dot),
+            "graphml" => Ok(OutputFormat::GraphMl),
+            _ => Err(format!("Unknown output format: {}", s)),
+        }
+    }
+}
+
+/// A line of the JSON Lines output.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record<'a> {
+    Function(&'a Node),
+    Macro(&'a Node),
+    FunctionCall(&'a Edge),
+    MacroCall(&'a MacroCall),
+    NestedMacroCall { caller: NodeId, callee: NodeId },
+}
+
+impl CallGraph {
+    /// Writes the call-graph to `writer` in the given `format`. The output is written
+    /// incrementally, without building it in memory first.
+    pub fn write<W: Write>(&self, format: OutputFormat, writer: &mut W) -> io::Result<()> {
+        match format {
+            OutputFormat::Json => {
+                serde_json::to_writer_pretty(&mut *writer, self)?;
+                writeln!(writer)
+            }
+            OutputFormat::JsonLines => self.write_json_lines(writer),
+            OutputFormat::Dot => self.write_dot(writer),
+            OutputFormat::GraphMl => self.write_graphml(writer),
+        }
+    }
+    fn write_json_lines<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for node in &self.functions {
+            write_json_line(writer, &Record::Function(node))?;
+        }
+        for node in &self.macros {
+            write_json_line(writer, &Record::Macro(node))?;
+        }
+        for edge in &self.function_calls {
+            write_json_line(writer, &Record::FunctionCall(edge))?;
+        }
+        for call in &self.macro_calls {
+            write_json_line(writer, &Record::MacroCall(call))?;
+        }
+        for (caller, callee) in &self.nested_macro_calls {
+            let record = Record::NestedMacroCall {
+                caller: *caller,
+                callee: *callee,
+            };
+            write_json_line(writer, &record)?;
+        }
+        Ok(())
+    }
+    fn write_dot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "digraph callgraph {{")?;
+        for node in &self.functions {
+            write_dot_node(writer, node, "function", "ellipse")?;
+        }
+        for node in &self.macros {
+            write_dot_node(writer, node, "macro", "box")?;
+        }
+        for edge in &self.function_calls {
+            // Static calls are drawn with solid lines, virtual calls with dashed lines and
+            // indirect calls with bold dashed lines. Speculative edges are gray.
+            let style = match edge.kind {
+                EdgeKind::Indirect => "\"dashed,bold\"",
+                kind if kind.is_static() => "solid",
+                _ => "dashed",
+            };
+            let color = match edge.kind.is_speculative() {
+                true => "gray",
+                false => "black",
+            };
+            writeln!(
+                writer,
+                "    n{} -> n{} [kind=\"{}\", num_calls={}, style={}, color={}];",
+                edge.caller,
+                edge.callee,
+                edge.kind.as_str(),
+                edge.num_calls,
+                style,
+                color
+            )?;
+        }
+        for call in &self.macro_calls {
+            write!(
+                writer,
+                "    n{} -> n{} [kind=\"macro\", style=dotted, color=gray",
+                call.caller, call.callee
+            )?;
+            if let Some(through) = call.through {
+                write!(writer, ", through=n{}", through)?;
+            }
+            writeln!(writer, "];")?;
+        }
+        for (caller, callee) in &self.nested_macro_calls {
+            writeln!(
+                writer,
+                "    n{} -> n{} [kind=\"nested-macro\", style=dotted, color=gray];",
+                caller, callee
+            )?;
+        }
+        writeln!(writer, "}}")
+    }
+    fn write_graphml<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            writer,
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+        )?;
+        // Attribute declarations: (id, domain, name, type).
+        let keys = [
+            ("d0", "node", "kind", "string"),
+            ("d1", "node", "package_name", "string"),
+            ("d2", "node", "package_version", "string"),
+            ("d3", "node", "crate_name", "string"),
+            ("d4", "node", "relative_def_id", "string"),
+            ("d5", "node", "is_externally_visible", "boolean"),
+            ("d6", "node", "num_lines", "int"),
+            ("d7", "node", "source_location", "string"),
+            ("d8", "edge", "kind", "string"),
+            ("d9", "edge", "num_calls", "int"),
+            ("d10", "edge", "through", "string"),
+        ];
+        for (id, domain, name, typ) in keys.iter() {
+            writeln!(
+                writer,
+                "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                id, domain, name, typ
+            )?;
+        }
+        writeln!(
+            writer,
+            "  <graph id=\"callgraph\" edgedefault=\"directed\">"
+        )?;
+        for node in &self.functions {
+            write_graphml_node(writer, node, "function")?;
+        }
+        for node in &self.macros {
+            write_graphml_node(writer, node, "macro")?;
+        }
+        for edge in &self.function_calls {
+            write_graphml_edge(
+                writer,
+                edge.caller,
+                edge.callee,
+                edge.kind.as_str(),
+                Some(edge.num_calls),
+            )?;
+        }
+        for call in &self.macro_calls {
+            write!(
+                writer,
+                "    <edge source=\"n{}\" target=\"n{}\"><data key=\"d8\">macro</data>",
+                call.caller, call.callee
+            )?;
+            if let Some(through) = call.through {
+                write!(writer, "<data key=\"d10\">n{}</data>", through)?;
+            }
+            writeln!(writer, "</edge>")?;
+        }
+        for (caller, callee) in &self.nested_macro_calls {
+            write_graphml_edge(writer, *caller, *callee, "nested-macro", None)?;
+        }
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")
+    }
+}
+
+fn write_dot_node<W: Write>(
+    writer: &mut W,
+    node: &Node,
+    kind: &str,
+    shape: &str,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "    n{} [label=\"{}\", kind=\"{}\", shape={}, package_name=\"{}\", \
+         package_version=\"{}\", crate_name=\"{}\", is_externally_visible={}, \
+         num_lines={}, source_location=\"{}\"];",
+        node.id,
+        escape_dot(&node.relative_def_id),
+        kind,
+        shape,
+        escape_dot(node.package_name.as_ref().map_or("", String::as_str)),
+        escape_dot(node.package_version.as_ref().map_or("", String::as_str)),
+        escape_dot(&node.crate_name),
+        node.is_externally_visible,
+        node.num_lines,
+        escape_dot(node.source_location.as_ref().map_or("", String::as_str)),
+    )
+}
+
+fn write_graphml_node<W: Write>(writer: &mut W, node: &Node, kind: &str) -> io::Result<()> {
+    writeln!(writer, "    <node id=\"n{}\">", node.id)?;
+    writeln!(writer, "      <data key=\"d0\">{}</data>", kind)?;
+    if let Some(package_name) = &node.package_name {
+        writeln!(
+            writer,
+            "      <data key=\"d1\">{}</data>",
+            escape_xml(package_name)
+        )?;
+    }
+    if let Some(package_version) = &node.package_version {
+        writeln!(
+            writer,
+            "      <data key=\"d2\">{}</data>",
+            escape_xml(package_version)
+        )?;
+    }
+    writeln!(
+        writer,
+        "      <data key=\"d3\">{}</data>",
+        escape_xml(&node.crate_name)
+    )?;
+    writeln!(
+        writer,
+        "      <data key=\"d4\">{}</data>",
+        escape_xml(&node.relative_def_id)
+    )?;
+    writeln!(
+        writer,
+        "      <data key=\"d5\">{}</data>",
+        node.is_externally_visible
+    )?;
+    writeln!(writer, "      <data key=\"d6\">{}</data>", node.num_lines)?;
+    if let Some(source_location) = &node.source_location {
+        writeln!(
+            writer,
+            "      <data key=\"d7\">{}</data>",
+            escape_xml(source_location)
+        )?;
+    }
+    writeln!(writer, "    </node>")
+}
+
+fn write_graphml_edge<W: Write>(
+    writer: &mut W,
+    caller: NodeId,
+    callee: NodeId,
+    kind: &str,
+    num_calls: Option<usize>,
+) -> io::Result<()> {
+    write!(
+        writer,
+        "    <edge source=\"n{}\" target=\"n{}\"><data key=\"d8\">{}</data>",
+        caller, callee, kind
+    )?;
+    if let Some(num_calls) = num_calls {
+        write!(writer, "<data key=\"d9\">{}</data>", num_calls)?;
+    }
+    writeln!(writer, "</edge>")
+}
+
+/// Writes `value` as a single line of JSON.
+pub(crate) fn write_json_line<W: Write, T: Serialize>(writer: &mut W, value: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writeln!(writer)
+}
+
+pub(crate) fn escape_dot(string: &str) -> String {
+    string.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub(crate) fn escape_xml(string: &str) -> String {
+    string
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,180 @@
+use crate::callgraph::{CallGraph, Node, NodeId};
+use crate::info::{FunctionsInfo, ModulesInfo};
+use crate::query::{Direction, EdgeFilter};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+#[derive(Serialize)]
+pub struct ForeignCall {
+    caller: NodeId,
+    caller_relative_def_id: String,
+    caller_package: Option<String>,
+    callee: NodeId,
+    callee_relative_def_id: String,
+    callee_package: Option<String>,
+    num_calls: usize,
+}
+
+/// The calls into the foreign items that are declared in `extern` blocks with the same ABI.
+#[derive(Serialize)]
+pub struct ForeignAbiCalls {
+    abi: String,
+    num_foreign_functions: usize,
+    calls: Vec<ForeignCall>,
+}
+
+#[derive(Serialize)]
+pub struct ExportedFunction {
+    id: NodeId,
+    package_name: Option<String>,
+    package_version: Option<String>,
+    relative_def_id: String,
+    abi: String,
+    is_externally_visible: bool,
+}
+
+#[derive(Serialize)]
+pub struct PackageFfiExposure {
+    package_name: Option<String>,
+    package_version: Option<String>,
+    num_public_functions: usize,
+    // Externally visible functions from which a foreign item can be called.
+    num_public_functions_reaching_ffi: usize,
+}
+
+/// The boundary between Rust and foreign code: the calls into foreign items, the functions that
+/// foreign code can call, and the packages whose public API can reach foreign code.
+#[derive(Serialize)]
+pub struct FfiReport {
+    foreign_calls: Vec<ForeignAbiCalls>,
+    exported_functions: Vec<ExportedFunction>,
+    packages_reaching_ffi: Vec<PackageFfiExposure>,
+}
+
+impl FfiReport {
+    pub(crate) fn new(
+        callgraph: &CallGraph,
+        functions: &FunctionsInfo,
+        modules: &ModulesInfo,
+        filter: EdgeFilter,
+    ) -> Self {
+        let nodes: HashMap<NodeId, &Node> = callgraph
+            .functions
+            .iter()
+            .map(|node| (node.id, node))
+            .collect();
+        let mut foreign_functions = HashMap::new();
+        let mut exported_functions = Vec::new();
+        for def_path in functions.iter_def_paths() {
+            let node = match callgraph.get_node_by_def_path(def_path) {
+                Some(node_id) => nodes[node_id],
+                None => continue,
+            };
+            if let Some(abi) = functions.foreign_item_abi(def_path, modules) {
+                foreign_functions.insert(node.id, abi);
+            } else if functions.has_foreign_abi(def_path) {
+                exported_functions.push(ExportedFunction {
+                    id: node.id,
+                    package_name: node.package_name.clone(),
+                    package_version: node.package_version.clone(),
+                    relative_def_id: node.relative_def_id.clone(),
+                    abi: functions.functions_abi(def_path).unwrap_or_default(),
+                    is_externally_visible: node.is_externally_visible,
+                });
+            }
+        }
+        exported_functions.sort_by(|a, b| {
+            (&a.package_name, &a.package_version, &a.relative_def_id).cmp(&(
+                &b.package_name,
+                &b.package_version,
+                &b.relative_def_id,
+            ))
+        });
+
+        let mut foreign_calls: BTreeMap<&str, ForeignAbiCalls> = BTreeMap::new();
+        for abi in foreign_functions.values() {
+            let abi_calls = foreign_calls
+                .entry(abi.as_str())
+                .or_insert_with(|| ForeignAbiCalls {
+                    abi: abi.clone(),
+                    num_foreign_functions: 0,
+                    calls: Vec::new(),
+                });
+            abi_calls.num_foreign_functions += 1;
+        }
+        for edge in &callgraph.function_calls {
+            if !filter.accepts(edge.kind) {
+                continue;
+            }
+            if let Some(abi) = foreign_functions.get(&edge.callee) {
+                let caller = nodes[&edge.caller];
+                let callee = nodes[&edge.callee];
+                foreign_calls
+                    .get_mut(abi.as_str())
+                    .unwrap()
+                    .calls
+                    .push(ForeignCall {
+                        caller: caller.id,
+                        caller_relative_def_id: caller.relative_def_id.clone(),
+                        caller_package: package_label(caller),
+                        callee: callee.id,
+                        callee_relative_def_id: callee.relative_def_id.clone(),
+                        callee_package: package_label(callee),
+                        num_calls: edge.num_calls,
+                    });
+            }
+        }
+        let mut foreign_calls: Vec<_> = foreign_calls.into_iter().map(|(_, calls)| calls).collect();
+        for abi_calls in &mut foreign_calls {
+            abi_calls.calls.sort_by(|a, b| {
+                (&a.caller_relative_def_id, &a.callee_relative_def_id)
+                    .cmp(&(&b.caller_relative_def_id, &b.callee_relative_def_id))
+            });
+        }
+
+        let foreign_nodes: Vec<NodeId> = foreign_functions.keys().cloned().collect();
+        let reaching_ffi =
+            callgraph.reachable_nodes(&foreign_nodes, Direction::Backward, None, filter);
+        let mut packages = BTreeMap::new();
+        for node in callgraph
+            .functions
+            .iter()
+            .filter(|node| node.is_externally_visible && !foreign_functions.contains_key(&node.id))
+        {
+            let package = packages
+                .entry((node.package_name.clone(), node.package_version.clone()))
+                .or_insert_with(|| PackageFfiExposure {
+                    package_name: node.package_name.clone(),
+                    package_version: node.package_version.clone(),
+                    num_public_functions: 0,
+                    num_public_functions_reaching_ffi: 0,
+                });
+            package.num_public_functions += 1;
+            if reaching_ffi.contains(&node.id) {
+                package.num_public_functions_reaching_ffi += 1;
+            }
+        }
+        Self {
+            foreign_calls,
+            exported_functions,
+            packages_reaching_ffi: packages
+                .into_iter()
+                .map(|(_, package)| package)
+                .filter(|package| package.num_public_functions_reaching_ffi > 0)
+                .collect(),
+        }
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)
+    }
+}
+
+// Returns a label of the form "package version".
+fn package_label(node: &Node) -> Option<String> {
+    match (&node.package_name, &node.package_version) {
+        (Some(name), Some(version)) => Some(format!("{} {}", name, version)),
+        _ => None,
+    }
+}
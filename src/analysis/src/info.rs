@@ -1,3 +1,5 @@
+use crate::callgraph::{FunctionDetails, FunctionKind};
+use crate::query::strip_disambiguators;
 use corpus_database::tables::{InterningTables, Tables};
 use corpus_database::types::*;
 use std::collections::{HashMap, HashSet};
@@ -62,6 +64,10 @@ impl<'a> InterningInfo<'a> {
     pub fn span_location_to_source_location(&self, location: SpanLocation) -> String {
         self.span_location_to_string(location)
     }
+    pub fn abi_to_string(&self, abi: Abi) -> String {
+        let interned_string = self.interning_tables.abis[abi];
+        self.interning_tables.strings[interned_string].clone()
+    }
     pub fn def_path_to_crate(&self, def_path: &DefPath) -> String {
         let (crate_name, _, _, _, _) = self.interning_tables.def_paths[*def_path];
         let interned_string = self.interning_tables.crate_names[crate_name];
@@ -82,8 +88,11 @@ impl<'a> InterningInfo<'a> {
 
 pub(crate) struct MacrosInfo<'a> {
     macros: HashMap<DefPath, (Module, Visibility, SpanLocation)>,
-    // Vector of macro calls (function_def_path, macro_def_path).
-    macro_calls: Vec<(DefPath, DefPath)>,
+    // Vector of macro calls (function_def_path, macro_def_path, outer_macro_def_path), where the
+    // outer macro is the one whose expansion contains the call, if any.
+    macro_calls: Vec<(DefPath, DefPath, Option<DefPath>)>,
+    // Vector of macro calls (outer_macro_def_path, macro_def_path) within macro expansions.
+    nested_macro_calls: Vec<(DefPath, DefPath)>,
     interning: InterningInfo<'a>,
 }
 
@@ -112,7 +121,19 @@ impl<'a> MacrosInfo<'a> {
         for (span, call_site, _, _) in tables.relations.spans.iter() {
             span_to_call_site.insert(*span, *call_site);
         }
+        // Mapping from an expansion span to the location of the expanded macro.
+        let mut expansions = HashMap::new();
+        for (span, _, location) in tables.relations.macro_expansions.iter() {
+            expansions.insert(*span, *location);
+        }
+        let resolve_macro = |location: &SpanLocation| {
+            def_location_to_def_path.get(location).cloned().or_else(|| {
+                let summary_key = interning.span_location_to_summary_key_string(*location);
+                summary_key_to_def_path.get(&summary_key).cloned()
+            })
+        };
         let mut macro_calls = Vec::new();
+        let mut nested_macro_calls = HashSet::new();
         let mut call_sites: HashSet<Span> = HashSet::default();
         for (span, _, location) in tables.relations.macro_expansions.iter() {
             let call_site = span_to_call_site[span];
@@ -120,32 +141,45 @@ impl<'a> MacrosInfo<'a> {
                 continue;
             }
             call_sites.insert(call_site);
+            let macro_def_path = match resolve_macro(location) {
+                Some(macro_def_path) => macro_def_path,
+                None => continue,
+            };
+            // If the call site is itself part of an expansion, the macro is invoked by the
+            // expansion of the outer macro.
+            let through = expansions.get(&call_site).and_then(resolve_macro);
+            if let Some(outer_def_path) = through {
+                nested_macro_calls.insert((outer_def_path, macro_def_path));
+            }
             if let Some(scope) = spans_root_scope.get(span) {
                 if let Some(function_def_path) = functions_scopes.get(&scope) {
-                    if let Some(macro_def_path) = def_location_to_def_path.get(location) {
-                        macro_calls.push((*function_def_path, *macro_def_path));
-                    } else {
-                        let summary_key = interning.span_location_to_summary_key_string(*location);
-                        if let Some(macro_def_path) = summary_key_to_def_path.get(&summary_key) {
-                            macro_calls.push((*function_def_path, *macro_def_path));
-                        }
-                    }
+                    macro_calls.push((*function_def_path, macro_def_path, through));
                 }
             }
         }
+        let mut nested_macro_calls: Vec<_> = nested_macro_calls.into_iter().collect();
+        nested_macro_calls.sort();
         Self {
             macros,
             macro_calls,
+            nested_macro_calls,
             interning,
         }
     }
     pub fn iter_def_paths(&self) -> impl Iterator<Item = &DefPath> {
         self.macros.iter().map(|(def_path, _)| def_path)
     }
-    pub fn iter_macro_calls(&self) -> impl Iterator<Item = (&DefPath, &DefPath)> {
+    pub fn iter_macro_calls(&self) -> impl Iterator<Item = (&DefPath, &DefPath, Option<&DefPath>)> {
         self.macro_calls
             .iter()
-            .map(|(function_def_path, macro_def_path)| (function_def_path, macro_def_path))
+            .map(|(function_def_path, macro_def_path, through)| {
+                (function_def_path, macro_def_path, through.as_ref())
+            })
+    }
+    pub fn iter_nested_macro_calls(&self) -> impl Iterator<Item = (&DefPath, &DefPath)> {
+        self.nested_macro_calls
+            .iter()
+            .map(|(outer_def_path, macro_def_path)| (outer_def_path, macro_def_path))
     }
     pub fn macros_num_lines(&self, def_path: &DefPath) -> i32 {
         if let Some((_, _, location)) = self.macros.get(def_path) {
@@ -177,6 +211,14 @@ impl<'a> MacrosInfo<'a> {
 
 pub(crate) struct FunctionsInfo<'a> {
     functions: HashMap<DefPath, (Module, Visibility, Option<SpanLocation>, bool)>,
+    // Mapping from function to its ABI.
+    function_abis: HashMap<DefPath, Abi>,
+    // Mapping from function to its item, unsafety and return type.
+    function_signatures: HashMap<DefPath, (Item, Unsafety, Type)>,
+    function_parameter_types: HashMap<Item, Vec<(FnParamIndex, Type)>>,
+    function_unsafe_use: HashMap<DefPath, bool>,
+    methods: HashSet<Item>,
+    generic_functions: HashSet<Item>,
     function_to_impl_item: HashMap<DefPath, Item>,
     function_to_trait_item: HashMap<DefPath, Item>,
     // Interning tables.
@@ -210,9 +252,13 @@ impl<'a> FunctionsInfo<'a> {
             macro_created_span_to_location.insert(*span, *location);
         }
         let mut functions = HashMap::new();
-        for (_, def_path, module, visibility, _, _, _) in
+        let mut function_abis = HashMap::new();
+        let mut function_signatures = HashMap::new();
+        for (item, def_path, module, visibility, unsafety, abi, return_type) in
             tables.relations.function_definitions.iter()
         {
+            function_abis.insert(*def_path, *abi);
+            function_signatures.insert(*def_path, (*item, *unsafety, *return_type));
             if let Some(scope) = functions_scopes.get(def_path) {
                 let span = scopes_spans[&scope];
                 if let Some(location) = macro_created_span_to_location.get(&span) {
@@ -238,6 +284,32 @@ impl<'a> FunctionsInfo<'a> {
                 functions.insert(*def_path, (*module, *visibility, None, false));
             }
         }
+        let mut function_parameter_types: HashMap<Item, Vec<_>> = HashMap::new();
+        for (function, index, typ) in tables.relations.function_parameter_types.iter() {
+            function_parameter_types
+                .entry(*function)
+                .or_default()
+                .push((*index, *typ));
+        }
+        for parameter_types in function_parameter_types.values_mut() {
+            parameter_types.sort();
+        }
+        let mut function_unsafe_use = HashMap::new();
+        for (def_path, uses_unsafe) in tables.relations.function_unsafe_use.iter() {
+            function_unsafe_use.insert(*def_path, *uses_unsafe);
+        }
+        let methods = tables
+            .relations
+            .method_definitions
+            .iter()
+            .map(|(function, _)| *function)
+            .collect();
+        let generic_functions = tables
+            .relations
+            .generic_functions
+            .iter()
+            .map(|(function,)| *function)
+            .collect();
         let mut function_to_impl_item = HashMap::new();
         for (impl_id, function_def_path, _) in tables.relations.trait_impl_items.iter() {
             function_to_impl_item.insert(*function_def_path, *impl_id);
@@ -248,6 +320,12 @@ impl<'a> FunctionsInfo<'a> {
         }
         Self {
             functions,
+            function_abis,
+            function_signatures,
+            function_parameter_types,
+            function_unsafe_use,
+            methods,
+            generic_functions,
             function_to_impl_item,
             function_to_trait_item,
             interning,
@@ -278,6 +356,103 @@ impl<'a> FunctionsInfo<'a> {
             None
         }
     }
+    pub fn functions_abi(&self, def_path: &DefPath) -> Option<String> {
+        self.function_abis
+            .get(def_path)
+            .map(|abi| self.interning.abi_to_string(*abi))
+    }
+    // Returns the kind, unsafety, ABI and signature of a function that is defined in the
+    // database.
+    pub fn function_details(
+        &self,
+        def_path: &DefPath,
+        types: &TypeInfo,
+    ) -> Option<FunctionDetails> {
+        let (item, unsafety, return_type) = self.function_signatures.get(def_path)?;
+        let abi = self.functions_abi(def_path)?;
+        let kind = match abi.as_str() {
+            "Closure" => FunctionKind::Closure,
+            "ForeignItem" => FunctionKind::Foreign,
+            _ if self.function_to_trait_item.contains_key(def_path) => FunctionKind::TraitMethod,
+            _ if self.methods.contains(item) => FunctionKind::Method,
+            _ => FunctionKind::Fn,
+        };
+        let parameter_types = self
+            .function_parameter_types
+            .get(item)
+            .into_iter()
+            .flatten()
+            .map(|(_, typ)| types.resolve_type(typ, &self.interning).0)
+            .collect();
+        let (return_type, _) = types.resolve_type(return_type, &self.interning);
+        Some(FunctionDetails {
+            kind,
+            unsafety: *unsafety,
+            abi,
+            uses_unsafe: self.function_unsafe_use.get(def_path).cloned(),
+            is_generic: self.generic_functions.contains(item),
+            parameter_types,
+            return_type,
+        })
+    }
+    // Returns true if the function can be called from outside of its crate without being
+    // called by another function of the crate, i.e., if it is externally visible, the `main`
+    // function of a binary crate, a trait impl item, or a function exported with a foreign ABI.
+    pub fn is_entry_point(
+        &self,
+        def_path: &DefPath,
+        modules: &ModulesInfo,
+        types: &TypeInfo,
+    ) -> bool {
+        if self.is_externally_visible(def_path, modules, types) {
+            return true;
+        }
+        if let Some(impl_item) = self.function_to_impl_item.get(def_path) {
+            if types.is_trait_impl(impl_item) {
+                return true;
+            }
+        }
+        if let Some((module, _, _, _)) = self.functions.get(def_path) {
+            if modules.is_bin_root_module(module) && self.function_name(def_path) == "main" {
+                return true;
+            }
+        }
+        self.has_foreign_abi(def_path)
+    }
+    // Returns true if the function is defined with a foreign ABI, e.g., `extern "C" fn`, and
+    // thus can be called by foreign code.
+    pub fn has_foreign_abi(&self, def_path: &DefPath) -> bool {
+        match self.functions_abi(def_path).as_ref().map(String::as_str) {
+            Some("Rust")
+            | Some("Closure")
+            | Some("ForeignItem")
+            | Some("rust-call")
+            | Some("rust-intrinsic")
+            | Some("platform-intrinsic")
+            | None => false,
+            Some(_) => true,
+        }
+    }
+    // Returns the ABI of the `extern` block that declares the function, if the function is a
+    // foreign item.
+    pub fn foreign_item_abi(&self, def_path: &DefPath, modules: &ModulesInfo) -> Option<String> {
+        if self.functions_abi(def_path).as_ref().map(String::as_str) != Some("ForeignItem") {
+            return None;
+        }
+        let (module, _, _, _) = self.functions.get(def_path)?;
+        let abi = match modules.foreign_module_abi(module) {
+            Some(abi) => self.interning.abi_to_string(abi),
+            None => String::from("unknown"),
+        };
+        Some(abi.trim_matches('"').to_string())
+    }
+    // Returns the name of the function, i.e., the last segment of its def path without the
+    // disambiguator.
+    fn function_name(&self, def_path: &DefPath) -> String {
+        let def_path_string = self.interning.def_path_to_string(def_path);
+        let name = def_path_string.rsplit("::").next().unwrap_or("");
+        name.split('[').next().unwrap_or("").to_string()
+    }
     pub fn is_externally_visible(
         &self,
         def_path: &DefPath,
@@ -314,7 +489,11 @@ impl<'a> FunctionsInfo<'a> {
 
 pub(crate) struct ModulesInfo {
     modules: HashMap<Module, (DefPath, Visibility, Module)>,
+    // Mapping from foreign module, i.e., `extern` block, to its ABI.
+    foreign_module_abis: HashMap<Module, Abi>,
     module_is_externally_visible: HashMap<Module, bool>,
+    // Root modules of binary crates.
+    bin_root_modules: HashSet<Module>,
 }
 
 impl ModulesInfo {
@@ -336,18 +515,33 @@ impl ModulesInfo {
             }
         }
         let mut modules = HashMap::new();
-        for (def_path, parent_module, module, _, visibility, _) in
+        let mut foreign_module_abis = HashMap::new();
+        for (def_path, parent_module, module, _, visibility, abi) in
             tables.relations.submodules.iter()
         {
             modules.insert(*module, (*def_path, *visibility, *parent_module));
+            let abi_string = &tables.interning_tables.strings[tables.interning_tables.abis[*abi]];
+            if abi_string != "NONE" {
+                foreign_module_abis.insert(*module, *abi);
+            }
         }
+        let bin_root_modules = root_modules_to_crate_type
+            .iter()
+            .filter(|(_, crate_type)| crate_type.as_str() == "bin")
+            .map(|(module, _)| *module)
+            .collect();
         let module_is_externally_visible =
             Self::compute_modules_external_visibility(&root_modules_to_crate_type, &modules);
         Self {
             modules,
+            foreign_module_abis,
             module_is_externally_visible,
+            bin_root_modules,
         }
     }
+    pub fn foreign_module_abi(&self, module: &Module) -> Option<Abi> {
+        self.foreign_module_abis.get(module).cloned()
+    }
     // Returns a mapping that specifies if the module is externally visible or not.
     fn compute_modules_external_visibility(
         root_modules_to_crate_type: &HashMap<Module, String>,
@@ -424,6 +618,9 @@ impl ModulesInfo {
             }
         }
     }
+    pub fn is_bin_root_module(&self, module: &Module) -> bool {
+        self.bin_root_modules.contains(module)
+    }
     pub fn is_externally_visible(&self, module: &Module) -> bool {
         if let Some(is_externally_visible) = self.module_is_externally_visible.get(module) {
             *is_externally_visible
@@ -435,6 +632,185 @@ impl ModulesInfo {
     }
 }
 
+pub(crate) struct CallSitesInfo<'a> {
+    // Mapping from call to the location of its call site and whether the call site is created
+    // by a macro expansion.
+    call_sites: HashMap<FunctionCall, (SpanLocation, bool)>,
+    interning: InterningInfo<'a>,
+}
+
+impl<'a> CallSitesInfo<'a> {
+    pub fn new(tables: &'a Tables) -> Self {
+        let interning = InterningInfo::new(&tables.interning_tables);
+        let mut spans = HashMap::new();
+        for (span, call_site, _, location) in tables.relations.spans.iter() {
+            spans.insert(*span, (*call_site, *location));
+        }
+        let mut macro_spans = HashSet::new();
+        for (span, _, _) in tables.relations.macro_expansions.iter() {
+            macro_spans.insert(*span);
+        }
+        let mut call_sites = HashMap::new();
+        for (call, span) in tables.relations.terminators_call_span.iter() {
+            // Follow the call sites of the expansions up to the span in the source code. The
+            // call site of a span that is not created by an expansion is not registered.
+            let mut span = *span;
+            let mut is_macro_expanded = false;
+            while let Some((call_site, _)) = spans.get(&span) {
+                is_macro_expanded |= macro_spans.contains(&span);
+                if !spans.contains_key(call_site) {
+                    break;
+                }
+                span = *call_site;
+            }
+            if let Some((_, location)) = spans.get(&span) {
+                call_sites.insert(*call, (*location, is_macro_expanded));
+            }
+        }
+        Self {
+            call_sites,
+            interning,
+        }
+    }
+    // Returns the location of the call site and whether it is created by a macro expansion.
+    pub fn call_site(&self, call: &FunctionCall) -> Option<(String, bool)> {
+        self.call_sites.get(call).map(|(location, is_macro_expanded)| {
+            let location = self.interning.span_location_to_source_location(*location);
+            (location, *is_macro_expanded)
+        })
+    }
+}
+
+pub(crate) struct AggregatesInfo {
+    // Mapping from function to the ADTs that are constructed in its body.
+    function_to_adts: HashMap<DefPath, HashSet<DefPath>>,
+}
+
+impl AggregatesInfo {
+    pub fn new(tables: &Tables) -> Self {
+        let mut adt_def_paths = HashMap::new();
+        for (typ, def_path, _, _, _) in tables.relations.types_adt_def.iter() {
+            adt_def_paths.insert(*typ, *def_path);
+        }
+        // Mapping from ADT aggregate statement to the constructed ADT.
+        let mut statements_adts = HashMap::new();
+        for (stmt, target_type, kind) in tables.relations.statements_assign_aggregate.iter() {
+            if let AggregateKind::Adt = kind {
+                if let Some(def_path) = adt_def_paths.get(target_type) {
+                    statements_adts.insert(*stmt, *def_path);
+                }
+            }
+        }
+        let mut blocks_adts: HashMap<BasicBlock, Vec<DefPath>> = HashMap::new();
+        for (stmt, block, _, _, _) in tables.relations.statements.iter() {
+            if let Some(def_path) = statements_adts.get(stmt) {
+                blocks_adts.entry(*block).or_default().push(*def_path);
+            }
+        }
+        let mut function_to_adts: HashMap<DefPath, HashSet<DefPath>> = HashMap::new();
+        for (block, mir, _) in tables.relations.basic_blocks.iter() {
+            if let Some(def_paths) = blocks_adts.get(block) {
+                function_to_adts
+                    .entry(*mir)
+                    .or_default()
+                    .extend(def_paths.iter().cloned());
+            }
+        }
+        Self { function_to_adts }
+    }
+    pub fn iter_constructed_adts(&self, function: &DefPath) -> impl Iterator<Item = &DefPath> {
+        self.function_to_adts.get(function).into_iter().flatten()
+    }
+    // Returns the ADTs that are constructed in the body of some function.
+    pub fn iter_adts_with_construction_sites(&self) -> impl Iterator<Item = &DefPath> {
+        self.function_to_adts.values().flatten()
+    }
+}
+
+pub(crate) struct AssertsInfo {
+    // Mapping from function to the kinds of the `Assert` terminators that it contains, e.g., an
+    // overflow or a bounds check.
+    functions_with_asserts: HashMap<DefPath, HashSet<AssertKind>>,
+}
+
+impl AssertsInfo {
+    pub fn new(tables: &Tables) -> Self {
+        let mut assert_kinds = HashMap::new();
+        for (block, kind) in tables.relations.terminators_assert_kind.iter() {
+            assert_kinds.insert(*block, *kind);
+        }
+        let mut assert_blocks = HashMap::new();
+        for (block, _, _, _, _) in tables.relations.terminators_assert.iter() {
+            let kind = assert_kinds
+                .get(block)
+                .cloned()
+                .unwrap_or(AssertKind::Unknown);
+            assert_blocks.insert(*block, kind);
+        }
+        let mut functions_with_asserts: HashMap<DefPath, HashSet<AssertKind>> = HashMap::new();
+        for (block, mir, _) in tables.relations.basic_blocks.iter() {
+            if let Some(kind) = assert_blocks.get(block) {
+                functions_with_asserts
+                    .entry(*mir)
+                    .or_default()
+                    .insert(*kind);
+            }
+        }
+        Self {
+            functions_with_asserts,
+        }
+    }
+    pub fn iter_functions_with_asserts(
+        &self,
+    ) -> impl Iterator<Item = (&DefPath, &HashSet<AssertKind>)> {
+        self.functions_with_asserts.iter()
+    }
+}
+
+pub(crate) struct UnsafeCodeInfo {
+    // Functions that use unsafe operations or contain an unsafe block.
+    unsafe_functions: HashSet<DefPath>,
+}
+
+impl UnsafeCodeInfo {
+    pub fn new(tables: &Tables) -> Self {
+        let mut unsafe_functions = HashSet::new();
+        for (def_path, uses_unsafe) in tables.relations.function_unsafe_use.iter() {
+            if *uses_unsafe {
+                unsafe_functions.insert(*def_path);
+            }
+        }
+        let mut root_scopes = HashMap::new();
+        for (_, def_path, root_scope) in tables.relations.mir_cfgs.iter() {
+            root_scopes.insert(*root_scope, *def_path);
+        }
+        let mut parent_scopes = HashMap::new();
+        let mut unsafe_scopes = Vec::new();
+        for (parent, child, safety, _) in tables.relations.subscopes.iter() {
+            parent_scopes.insert(*child, *parent);
+            match safety {
+                ScopeSafety::BuiltinUnsafe | ScopeSafety::ExplicitUnsafe => {
+                    unsafe_scopes.push(*child)
+                }
+                _ => {}
+            }
+        }
+        for mut scope in unsafe_scopes {
+            // Follow the parents up to the root scope of the function.
+            while let Some(parent) = parent_scopes.get(&scope) {
+                scope = *parent;
+            }
+            if let Some(def_path) = root_scopes.get(&scope) {
+                unsafe_functions.insert(*def_path);
+            }
+        }
+        Self { unsafe_functions }
+    }
+    pub fn iter_unsafe_functions(&self) -> impl Iterator<Item = &DefPath> {
+        self.unsafe_functions.iter()
+    }
+}
+
 pub(crate) struct TypeInfo {
     // Mapping from Adt to info.
     adts: HashMap<DefPath, (InternedString, Visibility, Module)>,
@@ -452,17 +828,34 @@ pub(crate) struct TypeInfo {
     pub trait_impl_to_items: HashMap<DefPath, HashMap<InternedString, DefPath>>,
     // Mapping from Trait Item DefPath to (Item Name, Item Defaultness, Trait DefPath).
     pub trait_items: HashMap<DefPath, (InternedString, Defaultness, DefPath)>,
+    // Mapping from Trait DefPath to its supertraits and whether they are auto traits.
+    supertraits: HashMap<DefPath, Vec<(DefPath, bool)>>,
 
     types_primitive: HashMap<Type, TyPrimitive>,
     types_slice: HashMap<Type, Type>,
     types_array: HashMap<Type, Type>,
+    types_array_len: HashMap<Type, u64>,
     types_raw_ptr: HashMap<Type, (Type, Mutability)>,
     types_ref: HashMap<Type, (Type, Mutability)>,
+    types_dynamic: HashSet<Type>,
+    // Mapping from `dyn` type to its principal trait.
     types_dynamic_trait: HashMap<Type, DefPath>,
+    types_dynamic_auto_traits: HashMap<Type, Vec<DefPath>>,
+    types_dynamic_projections: HashMap<Type, Vec<(DefPath, Type)>>,
     types_tuple: HashSet<Type>,
     types_tuple_elements: HashMap<Type, Vec<(TupleFieldIndex, Type)>>,
     types_projection: HashMap<Type, (DefPath, DefPath)>,
     types_param: HashMap<Type, String>,
+    // Mapping from type to its type arguments, ordered by index.
+    types_generic_args: HashMap<Type, Vec<(GenericArgIndex, Type)>>,
+    types_fn_def: HashMap<Type, DefPath>,
+    types_fn_ptr_params: HashMap<Type, Vec<(FnParamIndex, Type)>>,
+    types_fn_ptr_output: HashMap<Type, Type>,
+    types_closure: HashMap<Type, DefPath>,
+    types_generator: HashMap<Type, DefPath>,
+    types_generator_witness: HashSet<Type>,
+    types_opaque: HashMap<Type, DefPath>,
+    types_foreign: HashMap<Type, DefPath>,
 }
 
 impl TypeInfo {
@@ -515,6 +908,14 @@ impl TypeInfo {
             let trait_def_path = item_to_def_path[trait_id];
             trait_items.insert(*def_path, (*name, *defaultness, trait_def_path));
         }
+        let mut supertraits: HashMap<_, Vec<_>> = HashMap::new();
+        for (trait_id, supertrait_def_path, is_auto) in tables.relations.trait_supertraits.iter() {
+            let trait_def_path = item_to_def_path[trait_id];
+            supertraits
+                .entry(trait_def_path)
+                .or_default()
+                .push((*supertrait_def_path, *is_auto));
+        }
         let mut types_primitive = HashMap::new();
         for (typ, primitive) in tables.relations.types_primitive.iter() {
             types_primitive.insert(*typ, *primitive);
@@ -527,6 +928,10 @@ impl TypeInfo {
         for (typ, element_type) in tables.relations.types_array.iter() {
             types_array.insert(*typ, *element_type);
         }
+        let mut types_array_len = HashMap::new();
+        for (typ, len) in tables.relations.types_array_len.iter() {
+            types_array_len.insert(*typ, *len);
+        }
         let mut types_raw_ptr = HashMap::new();
         for (raw_ptr_type, typ, mutability) in tables.relations.types_raw_ptr.iter() {
             types_raw_ptr.insert(*raw_ptr_type, (*typ, *mutability));
@@ -535,12 +940,27 @@ impl TypeInfo {
         for (ref_type, typ, mutability) in tables.relations.types_ref.iter() {
             types_ref.insert(*ref_type, (*typ, *mutability));
         }
+        let mut types_dynamic = HashSet::new();
+        for (typ,) in tables.relations.types_dynamic.iter() {
+            types_dynamic.insert(*typ);
+        }
         let mut types_dynamic_trait = HashMap::new();
         for (typ, def_path) in tables.relations.types_dynamic_trait.iter() {
             types_dynamic_trait.insert(*typ, *def_path);
         }
+        let mut types_dynamic_auto_traits: HashMap<_, Vec<_>> = HashMap::new();
         for (typ, def_path) in tables.relations.types_dynamic_auto_trait.iter() {
-            types_dynamic_trait.insert(*typ, *def_path);
+            types_dynamic_auto_traits
+                .entry(*typ)
+                .or_default()
+                .push(*def_path);
+        }
+        let mut types_dynamic_projections: HashMap<_, Vec<_>> = HashMap::new();
+        for (typ, item, bound_type) in tables.relations.types_dynamic_projection.iter() {
+            types_dynamic_projections
+                .entry(*typ)
+                .or_default()
+                .push((*item, *bound_type));
         }
         let mut types_tuple = HashSet::new();
         for (typ,) in tables.relations.types_tuple.iter() {
@@ -563,6 +983,54 @@ impl TypeInfo {
         for (typ, def_path, item_def_path) in tables.relations.types_projection.iter() {
             types_projection.insert(*typ, (*def_path, *item_def_path));
         }
+        let mut types_generic_args: HashMap<_, Vec<_>> = HashMap::new();
+        for (typ, index, arg) in tables.relations.types_generic_args.iter() {
+            types_generic_args
+                .entry(*typ)
+                .or_default()
+                .push((*index, *arg));
+        }
+        for args in types_generic_args.values_mut() {
+            args.sort();
+        }
+        let mut types_fn_def = HashMap::new();
+        for (typ, def_path) in tables.relations.types_fn_def.iter() {
+            types_fn_def.insert(*typ, *def_path);
+        }
+        let mut types_fn_ptr_params: HashMap<_, Vec<_>> = HashMap::new();
+        for (typ, index, param_type) in tables.relations.types_fn_ptr_param.iter() {
+            types_fn_ptr_params
+                .entry(*typ)
+                .or_default()
+                .push((*index, *param_type));
+        }
+        for params in types_fn_ptr_params.values_mut() {
+            params.sort();
+        }
+        let mut types_fn_ptr_output = HashMap::new();
+        for (typ, output) in tables.relations.types_fn_ptr_output.iter() {
+            types_fn_ptr_output.insert(*typ, *output);
+        }
+        let mut types_closure = HashMap::new();
+        for (typ, def_path) in tables.relations.types_closure.iter() {
+            types_closure.insert(*typ, *def_path);
+        }
+        let mut types_generator = HashMap::new();
+        for (typ, def_path) in tables.relations.types_generator.iter() {
+            types_generator.insert(*typ, *def_path);
+        }
+        let mut types_generator_witness = HashSet::new();
+        for (typ,) in tables.relations.types_generator_witness.iter() {
+            types_generator_witness.insert(*typ);
+        }
+        let mut types_opaque = HashMap::new();
+        for (typ, def_path) in tables.relations.types_opaque.iter() {
+            types_opaque.insert(*typ, *def_path);
+        }
+        let mut types_foreign = HashMap::new();
+        for (typ, def_path) in tables.relations.types_foreign.iter() {
+            types_foreign.insert(*typ, *def_path);
+        }
         Self {
             adts,
             type_to_adt_def_path,
@@ -572,16 +1040,30 @@ impl TypeInfo {
             impls,
             trait_impl_to_items,
             trait_items,
+            supertraits,
             types_primitive,
             types_slice,
             types_array,
+            types_array_len,
             types_raw_ptr,
             types_ref,
+            types_dynamic,
             types_dynamic_trait,
+            types_dynamic_auto_traits,
+            types_dynamic_projections,
             types_tuple,
             types_tuple_elements,
             types_param,
             types_projection,
+            types_generic_args,
+            types_fn_def,
+            types_fn_ptr_params,
+            types_fn_ptr_output,
+            types_closure,
+            types_generator,
+            types_generator_witness,
+            types_opaque,
+            types_foreign,
         }
     }
     fn is_trait_impl(&self, impl_item: &Item) -> bool {
@@ -672,6 +1154,9 @@ impl TypeInfo {
             true
         }
     }
+    pub fn is_dynamic(&self, typ: &Type) -> bool {
+        self.types_dynamic.contains(typ)
+    }
     pub fn iter_adt_types(&self) -> impl Iterator<Item = &Type> {
         self.type_to_adt_def_path.iter().map(|(typ, _)| typ)
     }
@@ -681,83 +1166,257 @@ impl TypeInfo {
     pub fn iter_impl_def_paths(&self) -> impl Iterator<Item = &DefPath> {
         self.impls.iter().map(|(def_path, (_, _))| def_path)
     }
+    pub fn iter_supertraits(&self) -> impl Iterator<Item = (&DefPath, &DefPath, bool)> {
+        self.supertraits.iter().flat_map(|(def_path, supertraits)| {
+            supertraits
+                .iter()
+                .map(move |(supertrait_def_path, is_auto)| {
+                    (def_path, supertrait_def_path, *is_auto)
+                })
+        })
+    }
+    // Returns true if `supertrait_def_path` is a direct or transitive supertrait of
+    // `trait_def_path`.
+    pub fn is_supertrait(&self, trait_def_path: &DefPath, supertrait_def_path: &DefPath) -> bool {
+        let mut visited = HashSet::new();
+        let mut worklist = vec![*trait_def_path];
+        while let Some(def_path) = worklist.pop() {
+            if !visited.insert(def_path) {
+                continue;
+            }
+            for (supertrait, _) in self.supertraits.get(&def_path).into_iter().flatten() {
+                if supertrait == supertrait_def_path {
+                    return true;
+                }
+                worklist.push(*supertrait);
+            }
+        }
+        false
+    }
+    // Returns the self types of the impls of the given trait: the ADTs, whose generic arguments
+    // are ignored, and the other types.
+    pub fn implementing_types(
+        &self,
+        trait_def_path: &DefPath,
+    ) -> (HashSet<DefPath>, HashSet<Type>) {
+        let mut adts = HashSet::new();
+        let mut types = HashSet::new();
+        for impl_def_path in self
+            .trait_to_impls
+            .get(trait_def_path)
+            .into_iter()
+            .flatten()
+        {
+            match self.impl_adt(impl_def_path) {
+                Some(adt) => adts.insert(adt),
+                None => types.insert(self.impls[impl_def_path].1),
+            };
+        }
+        (adts, types)
+    }
+    // Returns true if the type is a type parameter, e.g., the self type of a blanket impl.
+    pub fn is_type_param(&self, typ: &Type) -> bool {
+        self.types_param.contains_key(typ)
+    }
     pub fn get_impl_types(&self, def_path: &DefPath) -> (Option<DefPath>, Type) {
         self.impls[def_path]
     }
+    // Returns the ADT that implements the given impl, if the self type of the impl is an ADT.
+    pub fn impl_adt(&self, impl_def_path: &DefPath) -> Option<DefPath> {
+        let (_, typ) = self.impls.get(impl_def_path)?;
+        self.type_to_adt_def_path.get(typ).cloned()
+    }
+    // Returns the string representation of the type, with the generic arguments of ADTs, and
+    // the def path that names it, looking through slices, arrays, pointers and references.
     pub(crate) fn resolve_type(
         &self,
         typ: &Type,
         interning: &InterningInfo,
     ) -> (String, Option<DefPath>) {
+        (self.type_to_string(typ, interning), self.type_def_path(typ))
+    }
+    fn type_to_string(&self, typ: &Type, interning: &InterningInfo) -> String {
+        let path =
+            |def_path: &DefPath| strip_disambiguators(&interning.def_path_to_string(def_path));
         if let Some(def_path) = self.type_to_adt_def_path.get(typ) {
-            let type_name = Self::def_path_to_type_name(def_path, interning);
-            (type_name, Some(*def_path))
+            let args = self.generic_args_to_strings(typ, interning);
+            format!("{}{}", path(def_path), Self::angle_bracketed(&args))
         } else if let Some(primitive) = self.types_primitive.get(typ) {
-            (Self::primitive_to_string(primitive), None)
+            Self::primitive_to_string(primitive)
         } else if let Some(element_type) = self.types_slice.get(typ) {
-            let (string_id, opt_def_path) = self.resolve_type(element_type, interning);
-            (format!("[{}]", string_id), opt_def_path)
+            format!("[{}]", self.type_to_string(element_type, interning))
         } else if let Some(element_type) = self.types_array.get(typ) {
-            let (string_id, opt_def_path) = self.resolve_type(element_type, interning);
-            (format!("[{}]", string_id), opt_def_path)
-        } else if let Some((typ, mutability)) = self.types_raw_ptr.get(typ) {
-            let (string_id, opt_def_path) = self.resolve_type(typ, interning);
-            match mutability.to_string().as_ref() {
-                "Mutable" => (format!("*mut {}", string_id), opt_def_path),
-                _ => (format!("*const {}", string_id), opt_def_path),
-            }
-        } else if let Some((typ, mutability)) = self.types_ref.get(typ) {
-            let (string_id, opt_def_path) = self.resolve_type(typ, interning);
-            (
-                format!(
-                    "&{} {}",
-                    Self::mutability_modifier_to_string(mutability),
-                    string_id
-                ),
-                opt_def_path,
+            let len = match self.types_array_len.get(typ) {
+                Some(len) => len.to_string(),
+                None => String::from("_"),
+            };
+            format!(
+                "[{}; {}]",
+                self.type_to_string(element_type, interning),
+                len
             )
-        } else if let Some(def_path) = self.types_dynamic_trait.get(typ) {
-            let type_name = Self::def_path_to_type_name(def_path, interning);
-            (format!("dyn {}", type_name), Some(*def_path))
-        } else if let Some(typ) = self.types_tuple.get(typ) {
-            if let Some(elements) = self.types_tuple_elements.get(typ) {
-                let mut tuple_string = String::from("(");
-                for (_, typ) in elements {
-                    let (string_id, _) = self.resolve_type(typ, interning);
-                    tuple_string.push_str(&string_id);
-                    tuple_string.push_str(", ");
+        } else if let Some((target_type, mutability)) = self.types_raw_ptr.get(typ) {
+            let modifier = match mutability {
+                Mutability::Mutable => "mut",
+                _ => "const",
+            };
+            format!(
+                "*{} {}",
+                modifier,
+                self.type_to_string(target_type, interning)
+            )
+        } else if let Some((target_type, mutability)) = self.types_ref.get(typ) {
+            let modifier = match mutability {
+                Mutability::Mutable => "mut ",
+                _ => "",
+            };
+            format!(
+                "&{}{}",
+                modifier,
+                self.type_to_string(target_type, interning)
+            )
+        } else if self.types_dynamic.contains(typ) {
+            let mut bounds = Vec::new();
+            if let Some(def_path) = self.types_dynamic_trait.get(typ) {
+                let mut args = self.generic_args_to_strings(typ, interning);
+                for (item, bound_type) in self
+                    .types_dynamic_projections
+                    .get(typ)
+                    .into_iter()
+                    .flatten()
+                {
+                    args.push(format!(
+                        "{} = {}",
+                        Self::def_path_to_name(item, interning),
+                        self.type_to_string(bound_type, interning)
+                    ));
                 }
-                tuple_string.push_str(")");
-                (tuple_string, None)
-            } else {
-                ("()".to_string(), None)
+                bounds.push(format!(
+                    "{}{}",
+                    path(def_path),
+                    Self::angle_bracketed(&args)
+                ));
+            }
+            for def_path in self
+                .types_dynamic_auto_traits
+                .get(typ)
+                .into_iter()
+                .flatten()
+            {
+                bounds.push(path(def_path));
+            }
+            format!("dyn {}", bounds.join(" + "))
+        } else if self.types_tuple.contains(typ) {
+            let elements: Vec<_> = self
+                .types_tuple_elements
+                .get(typ)
+                .into_iter()
+                .flatten()
+                .map(|(_, element_type)| self.type_to_string(element_type, interning))
+                .collect();
+            match elements.len() {
+                1 => format!("({},)", elements[0]),
+                _ => format!("({})", elements.join(", ")),
             }
         } else if let Some(param_type) = self.types_param.get(typ) {
-            (format!("{}: generic", param_type), None)
+            param_type.clone()
         } else if let Some((trait_def_path, item_def_path)) = self.types_projection.get(typ) {
-            (
-                interning.def_path_to_string(item_def_path),
-                Some(*trait_def_path),
+            // The first argument is the self type.
+            let mut args = self.generic_args_to_strings(typ, interning);
+            let self_type = match args.is_empty() {
+                true => String::from("_"),
+                false => args.remove(0),
+            };
+            format!(
+                "<{} as {}{}>::{}",
+                self_type,
+                path(trait_def_path),
+                Self::angle_bracketed(&args),
+                Self::def_path_to_name(item_def_path, interning)
+            )
+        } else if let Some(output) = self.types_fn_ptr_output.get(typ) {
+            let params: Vec<_> = self
+                .types_fn_ptr_params
+                .get(typ)
+                .into_iter()
+                .flatten()
+                .map(|(_, param_type)| self.type_to_string(param_type, interning))
+                .collect();
+            format!(
+                "fn({}) -> {}",
+                params.join(", "),
+                self.type_to_string(output, interning)
             )
+        } else if let Some(def_path) = self.types_fn_def.get(typ) {
+            let args = self.generic_args_to_strings(typ, interning);
+            format!("{{fn {}{}}}", path(def_path), Self::angle_bracketed(&args))
+        } else if let Some(def_path) = self.types_closure.get(typ) {
+            format!("{{closure {}}}", path(def_path))
+        } else if let Some(def_path) = self.types_generator.get(typ) {
+            format!("{{generator {}}}", path(def_path))
+        } else if self.types_generator_witness.contains(typ) {
+            String::from("{generator witness}")
+        } else if let Some(def_path) = self.types_opaque.get(typ) {
+            let args = self.generic_args_to_strings(typ, interning);
+            format!(
+                "{{opaque {}{}}}",
+                path(def_path),
+                Self::angle_bracketed(&args)
+            )
+        } else if let Some(def_path) = self.types_foreign.get(typ) {
+            path(def_path)
         } else {
             // Unknown type representing all the failed resolved types.
-            ("unknown".to_string(), None)
-        }
-    }
-    fn def_path_to_type_name(def_path: &DefPath, interning: &InterningInfo) -> String {
-        let def_path_string = interning.def_path_to_string(&def_path);
-        let mut tokens: Vec<&str> = def_path_string.split("::").collect();
-        let string_id = tokens.pop().unwrap();
-        let len = string_id.len();
-        let mut string_id = string_id.to_string();
-        string_id.truncate(len - 3);
-        string_id
-    }
-    fn mutability_modifier_to_string(mutability: &Mutability) -> String {
-        match mutability.to_string().as_ref() {
-            "Mutable" => String::from("mut"),
-            "Const" => String::from("const"),
-            _ => String::from(""),
+            "unknown".to_string()
+        }
+    }
+    fn type_def_path(&self, typ: &Type) -> Option<DefPath> {
+        if let Some(def_path) = self.type_to_adt_def_path.get(typ) {
+            Some(*def_path)
+        } else if let Some(element_type) = self.types_slice.get(typ) {
+            self.type_def_path(element_type)
+        } else if let Some(element_type) = self.types_array.get(typ) {
+            self.type_def_path(element_type)
+        } else if let Some((target_type, _)) = self.types_raw_ptr.get(typ) {
+            self.type_def_path(target_type)
+        } else if let Some((target_type, _)) = self.types_ref.get(typ) {
+            self.type_def_path(target_type)
+        } else if let Some(def_path) = self.types_dynamic_trait.get(typ) {
+            Some(*def_path)
+        } else if let Some(def_paths) = self.types_dynamic_auto_traits.get(typ) {
+            def_paths.first().cloned()
+        } else if let Some((trait_def_path, _)) = self.types_projection.get(typ) {
+            Some(*trait_def_path)
+        } else {
+            self.types_fn_def
+                .get(typ)
+                .or_else(|| self.types_closure.get(typ))
+                .or_else(|| self.types_generator.get(typ))
+                .or_else(|| self.types_opaque.get(typ))
+                .or_else(|| self.types_foreign.get(typ))
+                .cloned()
+        }
+    }
+    fn generic_args_to_strings(&self, typ: &Type, interning: &InterningInfo) -> Vec<String> {
+        self.types_generic_args
+            .get(typ)
+            .into_iter()
+            .flatten()
+            .map(|(_, arg)| self.type_to_string(arg, interning))
+            .collect()
+    }
+    fn angle_bracketed(args: &[String]) -> String {
+        match args.is_empty() {
+            true => String::new(),
+            false => format!("<{}>", args.join(", ")),
+        }
+    }
+    // Returns the last segment of the def path without its disambiguator.
+    fn def_path_to_name(def_path: &DefPath, interning: &InterningInfo) -> String {
+        let def_path_string = strip_disambiguators(&interning.def_path_to_string(&def_path));
+        match def_path_string.rfind("::") {
+            Some(index) => def_path_string[index + 2..].to_string(),
+            None => def_path_string,
         }
     }
     fn primitive_to_string(typ: &TyPrimitive) -> String {
@@ -778,7 +1437,7 @@ impl TypeInfo {
             "U128" => String::from("u128"),
             "F32" => String::from("f32"),
             "F64" => String::from("f64"),
-            "Str" => String::from("string"),
+            "Str" => String::from("str"),
             "Never" => String::from("!"),
             _ => String::from(""),
         }
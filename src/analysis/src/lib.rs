@@ -1,4 +1,17 @@
 pub mod analysis;
 pub mod callgraph;
+pub mod dead_code;
+pub mod dependencies;
+pub mod diff;
+pub mod export;
+pub mod ffi;
 mod info;
+pub mod metrics;
+pub mod panics;
+pub mod query;
+pub mod recursion;
+pub mod rules;
+pub mod server;
+pub mod shell;
 pub mod types;
+pub mod unsafe_reachability;
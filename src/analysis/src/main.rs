@@ -1,5 +1,14 @@
-use analysis::analysis::CallGraphAnalysis;
+use analysis::analysis::{CallGraphAnalysis, VirtualCallResolution};
+use analysis::dependencies::{DependencyGraph, DependencyLevel};
+use analysis::diff::CallGraphDiff;
+use analysis::export::OutputFormat;
+use analysis::metrics::{Metrics, MetricsLevel};
+use analysis::query::{Direction, EdgeFilter};
+use analysis::recursion::RecursionReport;
+use analysis::server::QueryServer;
 use corpus_database::tables::Tables;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
@@ -23,18 +32,302 @@ struct CMDArgs {
         help = "The file in which the type-hierarchy is stored."
     )]
     type_hierarchy_file_path: PathBuf,
+    #[structopt(
+        default_value = "json",
+        long = "format",
+        possible_values = &["json", "jsonl", "dot", "graphml"],
+        help = "The format in which the call-graph is written."
+    )]
+    format: OutputFormat,
+    #[structopt(
+        parse(from_os_str),
+        long = "output",
+        help = "The file to which the output is written instead of the standard output."
+    )]
+    output: Option<PathBuf>,
+    #[structopt(
+        default_value = "cha",
+        long = "virtual-calls",
+        possible_values = &["cha", "rta"],
+        help = "The analysis that resolves virtual calls: class hierarchy analysis or rapid type analysis."
+    )]
+    virtual_calls: VirtualCallResolution,
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    #[structopt(
+        name = "query",
+        about = "Compute the functions reachable from (or reaching) the given functions."
+    )]
+    Query {
+        #[structopt(
+            help = "The relative def path of the functions. The wildcards `*` and `?` are allowed."
+        )]
+        pattern: String,
+        #[structopt(
+            long,
+            help = "Follow the call edges backwards, i.e., compute the callers."
+        )]
+        backward: bool,
+        #[structopt(long, help = "The maximum number of call edges to follow.")]
+        depth: Option<usize>,
+        #[structopt(
+            default_value = "all",
+            long = "edges",
+            possible_values = &["all", "static", "virtual", "non-speculative"],
+            help = "The kind of call edges to follow."
+        )]
+        edges: EdgeFilter,
+    },
+    #[structopt(
+        name = "dead-code",
+        about = "Report the functions that are unreachable from the entry points of their package."
+    )]
+    DeadCode {
+        #[structopt(
+            default_value = "all",
+            long = "edges",
+            possible_values = &["all", "static", "virtual", "non-speculative"],
+            help = "The kind of call edges to follow."
+        )]
+        edges: EdgeFilter,
+    },
+    #[structopt(
+        name = "recursion",
+        about = "Report the recursive functions, i.e., the cycles in the call-graph."
+    )]
+    Recursion,
+    #[structopt(
+        name = "diff",
+        about = "Compare the call-graphs of two versions of the same package."
+    )]
+    Diff {
+        #[structopt(help = "The name of the package.")]
+        package_name: String,
+        #[structopt(help = "The old version of the package.")]
+        old_version: String,
+        #[structopt(help = "The new version of the package.")]
+        new_version: String,
+        #[structopt(
+            long,
+            help = "Match functions by their def paths without any disambiguators."
+        )]
+        ignore_disambiguators: bool,
+    },
+    #[structopt(
+        name = "metrics",
+        about = "Compute fan-in, fan-out and edge counts per function, crate or package."
+    )]
+    Metrics {
+        #[structopt(
+            default_value = "function",
+            long = "level",
+            possible_values = &["function", "crate", "package"],
+            help = "The granularity of the metrics."
+        )]
+        level: MetricsLevel,
+        #[structopt(long, help = "Output CSV instead of JSON.")]
+        csv: bool,
+    },
+    #[structopt(
+        name = "dependencies",
+        about = "Collapse the call-graph into a weighted graph of packages or crates."
+    )]
+    Dependencies {
+        #[structopt(
+            default_value = "package",
+            long = "level",
+            possible_values = &["package", "crate"],
+            help = "The granularity of the graph."
+        )]
+        level: DependencyLevel,
+    },
+    #[structopt(
+        name = "ffi",
+        about = "Report the calls into foreign functions and the functions that foreign code can call."
+    )]
+    Ffi {
+        #[structopt(
+            default_value = "all",
+            long = "edges",
+            possible_values = &["all", "static", "virtual", "non-speculative"],
+            help = "The kind of call edges to follow."
+        )]
+        edges: EdgeFilter,
+    },
+    #[structopt(
+        name = "panics",
+        about = "Report the externally visible functions that can reach a panic, grouped by package."
+    )]
+    Panics {
+        #[structopt(
+            default_value = "all",
+            long = "edges",
+            possible_values = &["all", "static", "virtual", "non-speculative"],
+            help = "The kind of call edges to follow."
+        )]
+        edges: EdgeFilter,
+        #[structopt(
+            long,
+            help = "Report all functions, not only the externally visible ones."
+        )]
+        all_functions: bool,
+        #[structopt(
+            long,
+            help = "Do not treat arithmetic overflow checks as panic entry points."
+        )]
+        exclude_overflow: bool,
+    },
+    #[structopt(
+        name = "unsafe-reachability",
+        about = "Report the functions that can reach unsafe code, grouped by package."
+    )]
+    UnsafeReachability {
+        #[structopt(
+            default_value = "all",
+            long = "edges",
+            possible_values = &["all", "static", "virtual", "non-speculative"],
+            help = "The kind of call edges to follow."
+        )]
+        edges: EdgeFilter,
+    },
+    #[structopt(
+        name = "serve",
+        about = "Answer JSON-over-HTTP queries about the call-graph and the type-hierarchy on localhost."
+    )]
+    Serve {
+        #[structopt(
+            default_value = "8000",
+            long = "port",
+            help = "The port on which the server listens."
+        )]
+        port: u16,
+        #[structopt(
+            default_value = "4",
+            long = "workers",
+            help = "The number of threads that handle the connections."
+        )]
+        workers: usize,
+    },
 }
 
 fn main() {
     let args = CMDArgs::from_args();
+    // The output file is created before the analysis runs, so that an invalid path is reported
+    // right away.
+    let stdout = std::io::stdout();
+    let mut writer: Box<dyn Write> = match &args.output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(error) => {
+                eprintln!(
+                    "Failed to create the output file {}: {}",
+                    path.display(),
+                    error
+                );
+                std::process::exit(1);
+            }
+        },
+        None => Box::new(BufWriter::new(stdout.lock())),
+    };
     let database_root = Path::new(&args.database_root);
     let tables = Tables::load_multifile(database_root).unwrap();
-    let analysis = CallGraphAnalysis::new(&tables);
+    let analysis = CallGraphAnalysis::new(&tables, args.virtual_calls);
     // println!("Loaded database");
 
     let callgraph = analysis.run();
-    println!("{}", serde_json::to_string_pretty(&callgraph).unwrap());
+    match args.cmd {
+        None => {
+            callgraph.write(args.format, &mut writer).unwrap();
 
-    let types = analysis.types();
-    types.save(&args.type_hierarchy_file_path);
+            let types = analysis.types();
+            types.save(&args.type_hierarchy_file_path);
+        }
+        Some(Command::Query {
+            pattern,
+            backward,
+            depth,
+            edges,
+        }) => {
+            let roots = callgraph.find_functions(&pattern);
+            if roots.is_empty() {
+                eprintln!("No function matches the pattern: {}", pattern);
+            }
+            let direction = match backward {
+                true => Direction::Backward,
+                false => Direction::Forward,
+            };
+            let subgraph = callgraph.reachable(&roots, direction, depth, edges);
+            subgraph.write(args.format, &mut writer).unwrap();
+        }
+        Some(Command::DeadCode { edges }) => {
+            let report = analysis.dead_code(&callgraph, edges);
+            report.write(&mut writer).unwrap();
+        }
+        Some(Command::Recursion) => {
+            let report = RecursionReport::new(&callgraph);
+            report.write(&mut writer).unwrap();
+        }
+        Some(Command::Diff {
+            package_name,
+            old_version,
+            new_version,
+            ignore_disambiguators,
+        }) => {
+            match CallGraphDiff::new(
+                &callgraph,
+                &package_name,
+                &old_version,
+                &new_version,
+                ignore_disambiguators,
+            ) {
+                Ok(diff) => diff.write(&mut writer).unwrap(),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Command::Metrics { level, csv }) => {
+            let metrics = Metrics::new(&callgraph);
+            match csv {
+                true => metrics.write_csv(level, &mut writer).unwrap(),
+                false => metrics.write_json(level, &mut writer).unwrap(),
+            }
+        }
+        Some(Command::Dependencies { level }) => {
+            let dependencies = DependencyGraph::new(&callgraph, level);
+            dependencies.write(args.format, &mut writer).unwrap();
+        }
+        Some(Command::Ffi { edges }) => {
+            let report = analysis.ffi(&callgraph, edges);
+            report.write(&mut writer).unwrap();
+        }
+        Some(Command::Panics {
+            edges,
+            all_functions,
+            exclude_overflow,
+        }) => {
+            let report =
+                analysis.panic_reachability(&callgraph, edges, all_functions, exclude_overflow);
+            report.write(&mut writer).unwrap();
+        }
+        Some(Command::UnsafeReachability { edges }) => {
+            let report = analysis.unsafe_reachability(&callgraph, edges);
+            report.write(&mut writer).unwrap();
+        }
+        Some(Command::Serve { port, workers }) => {
+            let types = analysis.types();
+            let server = QueryServer::new(&callgraph, &types);
+            eprintln!("Listening on http://127.0.0.1:{}", port);
+            server.serve(("127.0.0.1", port), workers).unwrap();
+        }
+    }
+    if let Err(error) = writer.flush() {
+        eprintln!("Failed to write the output: {}", error);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,290 @@
+use crate::callgraph::{CallGraph, Node, NodeId};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// The granularity at which the metrics are reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricsLevel {
+    Function,
+    Crate,
+    Package,
+}
+
+impl FromStr for MetricsLevel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "function" => Ok(MetricsLevel::Function),
+            "crate" => Ok(MetricsLevel::Crate),
+            "package" => Ok(MetricsLevel::Package),
+            _ => Err(format!("Unknown metrics level: {}", s)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct FunctionMetrics {
+    id: NodeId,
+    package_name: Option<String>,
+    package_version: Option<String>,
+    crate_name: String,
+    relative_def_id: String,
+    // The number of distinct callers.
+    fan_in: usize,
+    // The number of distinct callees.
+    fan_out: usize,
+    num_static_out_edges: usize,
+    num_virtual_out_edges: usize,
+    num_macro_invocations: usize,
+}
+
+#[derive(Default, Serialize)]
+pub struct GroupMetrics {
+    package_name: Option<String>,
+    package_version: Option<String>,
+    // `None` for package level metrics.
+    crate_name: Option<String>,
+    num_functions: usize,
+    num_macros: usize,
+    num_edges: usize,
+    num_static_edges: usize,
+    num_virtual_edges: usize,
+    // The number of edges whose callee is in the same crate (or package) as the caller.
+    num_internal_edges: usize,
+    internal_edges_share: f64,
+    num_macro_invocations: usize,
+}
+
+/// Fan-in, fan-out and edge counts of the call-graph.
+pub struct Metrics {
+    functions: Vec<FunctionMetrics>,
+    crates: Vec<GroupMetrics>,
+    packages: Vec<GroupMetrics>,
+}
+
+impl Metrics {
+    pub fn new(callgraph: &CallGraph) -> Self {
+        let nodes: HashMap<NodeId, &Node> = callgraph
+            .functions
+            .iter()
+            .chain(callgraph.macros.iter())
+            .map(|node| (node.id, node))
+            .collect();
+        let mut callers: HashMap<NodeId, HashSet<NodeId>> = HashMap::new();
+        let mut callees: HashMap<NodeId, HashSet<NodeId>> = HashMap::new();
+        let mut static_out_edges: HashMap<NodeId, usize> = HashMap::new();
+        let mut virtual_out_edges: HashMap<NodeId, usize> = HashMap::new();
+        for edge in &callgraph.function_calls {
+            callers.entry(edge.callee).or_default().insert(edge.caller);
+            callees.entry(edge.caller).or_default().insert(edge.callee);
+            let out_edges = match edge.kind.is_static() {
+                true => &mut static_out_edges,
+                false => &mut virtual_out_edges,
+            };
+            *out_edges.entry(edge.caller).or_default() += 1;
+        }
+        let mut macro_invocations: HashMap<NodeId, usize> = HashMap::new();
+        for call in &callgraph.macro_calls {
+            *macro_invocations.entry(call.caller).or_default() += 1;
+        }
+        let count = |map: &HashMap<NodeId, usize>, id| map.get(id).cloned().unwrap_or(0);
+        let functions = callgraph
+            .functions
+            .iter()
+            .map(|node| FunctionMetrics {
+                id: node.id,
+                package_name: node.package_name.clone(),
+                package_version: node.package_version.clone(),
+                crate_name: node.crate_name.clone(),
+                relative_def_id: node.relative_def_id.clone(),
+                fan_in: callers.get(&node.id).map_or(0, HashSet::len),
+                fan_out: callees.get(&node.id).map_or(0, HashSet::len),
+                num_static_out_edges: count(&static_out_edges, &node.id),
+                num_virtual_out_edges: count(&virtual_out_edges, &node.id),
+                num_macro_invocations: count(&macro_invocations, &node.id),
+            })
+            .collect();
+
+        let crate_key = |node: &Node| {
+            (
+                node.package_name.clone(),
+                node.package_version.clone(),
+                Some(node.crate_name.clone()),
+            )
+        };
+        let package_key = |node: &Node| {
+            (
+                node.package_name.clone(),
+                node.package_version.clone(),
+                None,
+            )
+        };
+        let crates = Self::aggregate(callgraph, &nodes, crate_key);
+        let packages = Self::aggregate(callgraph, &nodes, package_key);
+        Self {
+            functions,
+            crates,
+            packages,
+        }
+    }
+    // Aggregates the metrics of the nodes that have the same key.
+    fn aggregate<F>(
+        callgraph: &CallGraph,
+        nodes: &HashMap<NodeId, &Node>,
+        key: F,
+    ) -> Vec<GroupMetrics>
+    where
+        F: Fn(&Node) -> GroupKey,
+    {
+        let mut groups = BTreeMap::new();
+        for node in &callgraph.functions {
+            group(&mut groups, key(node)).num_functions += 1;
+        }
+        for node in &callgraph.macros {
+            group(&mut groups, key(node)).num_macros += 1;
+        }
+        for edge in &callgraph.function_calls {
+            let caller_key = key(nodes[&edge.caller]);
+            let is_internal = caller_key == key(nodes[&edge.callee]);
+            let metrics = group(&mut groups, caller_key);
+            metrics.num_edges += 1;
+            match edge.kind.is_static() {
+                true => metrics.num_static_edges += 1,
+                false => metrics.num_virtual_edges += 1,
+            }
+            if is_internal {
+                metrics.num_internal_edges += 1;
+            }
+        }
+        for call in &callgraph.macro_calls {
+            group(&mut groups, key(nodes[&call.caller])).num_macro_invocations += 1;
+        }
+        let mut groups: Vec<_> = groups.into_iter().map(|(_, metrics)| metrics).collect();
+        for metrics in &mut groups {
+            if metrics.num_edges > 0 {
+                metrics.internal_edges_share =
+                    metrics.num_internal_edges as f64 / metrics.num_edges as f64;
+            }
+        }
+        groups
+    }
+    pub fn write_json<W: Write>(&self, level: MetricsLevel, writer: &mut W) -> io::Result<()> {
+        match level {
+            MetricsLevel::Function => serde_json::to_writer_pretty(&mut *writer, &self.functions)?,
+            MetricsLevel::Crate => serde_json::to_writer_pretty(&mut *writer, &self.crates)?,
+            MetricsLevel::Package => serde_json::to_writer_pretty(&mut *writer, &self.packages)?,
+        }
+        writeln!(writer)
+    }
+    pub fn write_csv<W: Write>(&self, level: MetricsLevel, writer: &mut W) -> io::Result<()> {
+        match level {
+            MetricsLevel::Function => {
+                write_csv_row(
+                    writer,
+                    &[
+                        "id",
+                        "package_name",
+                        "package_version",
+                        "crate_name",
+                        "relative_def_id",
+                        "fan_in",
+                        "fan_out",
+                        "num_static_out_edges",
+                        "num_virtual_out_edges",
+                        "num_macro_invocations",
+                    ],
+                )?;
+                for metrics in &self.functions {
+                    write_csv_row(
+                        writer,
+                        &[
+                            &metrics.id.to_string(),
+                            metrics.package_name.as_ref().map_or("", String::as_str),
+                            metrics.package_version.as_ref().map_or("", String::as_str),
+                            &metrics.crate_name,
+                            &metrics.relative_def_id,
+                            &metrics.fan_in.to_string(),
+                            &metrics.fan_out.to_string(),
+                            &metrics.num_static_out_edges.to_string(),
+                            &metrics.num_virtual_out_edges.to_string(),
+                            &metrics.num_macro_invocations.to_string(),
+                        ],
+                    )?;
+                }
+            }
+            MetricsLevel::Crate | MetricsLevel::Package => {
+                let groups = match level {
+                    MetricsLevel::Crate => &self.crates,
+                    _ => &self.packages,
+                };
+                write_csv_row(
+                    writer,
+                    &[
+                        "package_name",
+                        "package_version",
+                        "crate_name",
+                        "num_functions",
+                        "num_macros",
+                        "num_edges",
+                        "num_static_edges",
+                        "num_virtual_edges",
+                        "num_internal_edges",
+                        "internal_edges_share",
+                        "num_macro_invocations",
+                    ],
+                )?;
+                for metrics in groups {
+                    write_csv_row(
+                        writer,
+                        &[
+                            metrics.package_name.as_ref().map_or("", String::as_str),
+                            metrics.package_version.as_ref().map_or("", String::as_str),
+                            metrics.crate_name.as_ref().map_or("", String::as_str),
+                            &metrics.num_functions.to_string(),
+                            &metrics.num_macros.to_string(),
+                            &metrics.num_edges.to_string(),
+                            &metrics.num_static_edges.to_string(),
+                            &metrics.num_virtual_edges.to_string(),
+                            &metrics.num_internal_edges.to_string(),
+                            &metrics.internal_edges_share.to_string(),
+                            &metrics.num_macro_invocations.to_string(),
+                        ],
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+type GroupKey = (Option<String>, Option<String>, Option<String>);
+
+// Returns the metrics of the group with the given key, creating them if needed.
+fn group(groups: &mut BTreeMap<GroupKey, GroupMetrics>, key: GroupKey) -> &mut GroupMetrics {
+    groups.entry(key.clone()).or_insert_with(|| {
+        let (package_name, package_version, crate_name) = key;
+        GroupMetrics {
+            package_name,
+            package_version,
+            crate_name,
+            ..GroupMetrics::default()
+        }
+    })
+}
+
+/// Writes a CSV row quoting the fields that contain separators or quotes.
+pub(crate) fn write_csv_row<W: Write>(writer: &mut W, fields: &[&str]) -> io::Result<()> {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains(|c| c == ',' || c == '"' || c == '\n') {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+    writeln!(writer, "{}", fields.join(","))
+}
@@ -0,0 +1,166 @@
+use crate::callgraph::{CallGraph, NodeId};
+use crate::info::AssertsInfo;
+use crate::query::{glob_match, strip_disambiguators, EdgeFilter};
+use corpus_database::types::AssertKind;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+
+/// The kind of a panic entry point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PanicSource {
+    /// A function of `core::panicking` or `std::panicking::begin_panic`.
+    PanicFunction,
+    /// `unwrap` or `expect` of `Option` or `Result`.
+    Unwrap,
+    /// A function that contains an `Assert` terminator of a bounds check.
+    BoundsCheck,
+    /// A function that contains an `Assert` terminator of an arithmetic overflow check.
+    Overflow,
+    /// A function that contains an `Assert` terminator of a division or remainder by zero check.
+    DivisionByZero,
+    /// A function that contains an `Assert` terminator of another or an unknown kind.
+    Assert,
+}
+
+// The panic sources of the assert kinds, in the order in which they are reported if a function
+// contains asserts of several kinds.
+const ASSERT_SOURCES: &[(AssertKind, PanicSource)] = &[
+    (AssertKind::BoundsCheck, PanicSource::BoundsCheck),
+    (AssertKind::DivisionByZero, PanicSource::DivisionByZero),
+    (AssertKind::Overflow, PanicSource::Overflow),
+    (AssertKind::Unknown, PanicSource::Assert),
+];
+
+// Patterns of the relative def paths (without disambiguators) of the panic entry points.
+const PANIC_FUNCTIONS: &[&str] = &["core::panicking::*", "std::panicking::begin_panic*"];
+const UNWRAP_FUNCTIONS: &[&str] = &[
+    "core::option::*::unwrap",
+    "core::option::*::expect",
+    "core::result::*::unwrap",
+    "core::result::*::expect",
+];
+
+#[derive(Serialize)]
+pub struct FunctionPanicReachability {
+    id: NodeId,
+    relative_def_id: String,
+    is_externally_visible: bool,
+    can_panic: bool,
+    // The kind of the panic entry point at the end of the path.
+    reason: Option<PanicSource>,
+    // A shortest call path from the function to a panic entry point. The path starts with the
+    // function itself and is empty if the function cannot panic.
+    path: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct PackagePanicReachability {
+    package_name: Option<String>,
+    package_version: Option<String>,
+    num_public_functions: usize,
+    num_panic_free_public_functions: usize,
+    functions: Vec<FunctionPanicReachability>,
+}
+
+/// For every externally visible function (or every function), whether it can transitively
+/// reach a panic entry point, grouped by package. Overflow checks can be excluded, since they
+/// are disabled in release builds by default.
+#[derive(Serialize)]
+pub struct PanicReachabilityReport {
+    packages: Vec<PackagePanicReachability>,
+}
+
+impl PanicReachabilityReport {
+    pub(crate) fn new(
+        callgraph: &CallGraph,
+        asserts: &AssertsInfo,
+        filter: EdgeFilter,
+        all_functions: bool,
+        exclude_overflow: bool,
+    ) -> Self {
+        let mut sources = HashMap::new();
+        for (def_path, kinds) in asserts.iter_functions_with_asserts() {
+            let source = ASSERT_SOURCES
+                .iter()
+                .filter(|(kind, _)| !(exclude_overflow && *kind == AssertKind::Overflow))
+                .find(|(kind, _)| kinds.contains(kind))
+                .map(|(_, source)| *source);
+            if let (Some(node_id), Some(source)) =
+                (callgraph.get_node_by_def_path(def_path), source)
+            {
+                sources.insert(*node_id, source);
+            }
+        }
+        // Calls to the panic functions take precedence over the asserts in their bodies.
+        for node in &callgraph.functions {
+            let def_path = strip_disambiguators(&node.relative_def_id);
+            let matches = |patterns: &[&str]| {
+                patterns
+                    .iter()
+                    .any(|pattern| glob_match(pattern, &def_path))
+            };
+            if matches(PANIC_FUNCTIONS) {
+                sources.insert(node.id, PanicSource::PanicFunction);
+            } else if matches(UNWRAP_FUNCTIONS) {
+                sources.insert(node.id, PanicSource::Unwrap);
+            }
+        }
+        let targets: HashSet<NodeId> = sources.keys().cloned().collect();
+        let next_hops = callgraph.shortest_path_next_hops(&targets, filter);
+        let relative_def_ids: HashMap<NodeId, &str> = callgraph
+            .functions
+            .iter()
+            .map(|node| (node.id, node.relative_def_id.as_str()))
+            .collect();
+        let mut packages = BTreeMap::new();
+        for node in &callgraph.functions {
+            if !all_functions && !node.is_externally_visible {
+                continue;
+            }
+            let package = packages
+                .entry((node.package_name.clone(), node.package_version.clone()))
+                .or_insert_with(|| PackagePanicReachability {
+                    package_name: node.package_name.clone(),
+                    package_version: node.package_version.clone(),
+                    num_public_functions: 0,
+                    num_panic_free_public_functions: 0,
+                    functions: Vec::new(),
+                });
+            let mut path = Vec::new();
+            let mut reason = None;
+            if targets.contains(&node.id) || next_hops.contains_key(&node.id) {
+                let mut current = node.id;
+                path.push(relative_def_ids[&current].to_string());
+                while let Some(next) = next_hops.get(&current) {
+                    current = *next;
+                    path.push(relative_def_ids[&current].to_string());
+                }
+                reason = sources.get(&current).cloned();
+            }
+            let can_panic = reason.is_some();
+            if node.is_externally_visible {
+                package.num_public_functions += 1;
+                if !can_panic {
+                    package.num_panic_free_public_functions += 1;
+                }
+            }
+            package.functions.push(FunctionPanicReachability {
+                id: node.id,
+                relative_def_id: node.relative_def_id.clone(),
+                is_externally_visible: node.is_externally_visible,
+                can_panic,
+                reason,
+                path,
+            });
+        }
+        Self {
+            packages: packages.into_iter().map(|(_, package)| package).collect(),
+        }
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)
+    }
+}
@@ -0,0 +1,270 @@
+use crate::callgraph::{CallGraph, EdgeKind, NodeId};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+
+/// The direction in which the call-graph is traversed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// From callers to callees.
+    Forward,
+    /// From callees to callers.
+    Backward,
+}
+
+/// The kinds of call edges that are followed during a traversal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeFilter {
+    All,
+    Static,
+    Virtual,
+    /// All edges except the speculative ones of calls to generic functions and indirect calls.
+    NonSpeculative,
+}
+
+impl EdgeFilter {
+    pub(crate) fn accepts(&self, kind: EdgeKind) -> bool {
+        match self {
+            EdgeFilter::All => true,
+            EdgeFilter::Static => kind.is_static(),
+            EdgeFilter::Virtual => !kind.is_static(),
+            EdgeFilter::NonSpeculative => !kind.is_speculative(),
+        }
+    }
+}
+
+impl FromStr for EdgeFilter {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(EdgeFilter::All),
+            "static" => Ok(EdgeFilter::Static),
+            "virtual" => Ok(EdgeFilter::Virtual),
+            "non-speculative" => Ok(EdgeFilter::NonSpeculative),
+            _ => Err(format!("Unknown edge filter: {}", s)),
+        }
+    }
+}
+
+impl CallGraph {
+    /// Returns the function nodes whose relative def path matches `pattern`.
+    ///
+    /// The pattern may contain the `*` and `?` wildcards. It is matched both against the
+    /// relative def path as is and against the path with the crate and item disambiguators
+    /// removed, e.g., `std::process::exit` matches `std[2f14]::process[0]::exit[0]`.
+    pub fn find_functions(&self, pattern: &str) -> Vec<NodeId> {
+        self.functions
+            .iter()
+            .filter(|node| def_path_matches(pattern, &node.relative_def_id))
+            .map(|node| node.id)
+            .collect()
+    }
+    /// Computes the sub-graph of the functions that are reachable from `roots` in the given
+    /// `direction` within `depth` hops (unbounded if `None`), following only the call edges
+    /// accepted by `filter`.
+    pub fn reachable(
+        &self,
+        roots: &[NodeId],
+        direction: Direction,
+        depth: Option<usize>,
+        filter: EdgeFilter,
+    ) -> CallGraph {
+        let (visited, edges) = self.traverse(roots, direction, depth, filter);
+        self.subgraph(&visited, &edges)
+    }
+    /// Computes the set of nodes that are reachable from `roots` (including the roots).
+    pub fn reachable_nodes(
+        &self,
+        roots: &[NodeId],
+        direction: Direction,
+        depth: Option<usize>,
+        filter: EdgeFilter,
+    ) -> HashSet<NodeId> {
+        let (visited, _) = self.traverse(roots, direction, depth, filter);
+        visited
+    }
+    /// Computes the next function on a shortest path from every function that reaches one of
+    /// the `targets` (but is not one of them), by a breadth-first traversal of the reversed
+    /// call-graph.
+    pub(crate) fn shortest_path_next_hops(
+        &self,
+        targets: &HashSet<NodeId>,
+        filter: EdgeFilter,
+    ) -> HashMap<NodeId, NodeId> {
+        let mut callers: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        for edge in &self.function_calls {
+            if filter.accepts(edge.kind) {
+                callers.entry(edge.callee).or_default().push(edge.caller);
+            }
+        }
+        let mut next_hops = HashMap::new();
+        let mut queue: VecDeque<NodeId> = targets.iter().cloned().collect();
+        while let Some(node) = queue.pop_front() {
+            for caller in callers.get(&node).into_iter().flatten() {
+                if !targets.contains(caller) && !next_hops.contains_key(caller) {
+                    next_hops.insert(*caller, node);
+                    queue.push_back(*caller);
+                }
+            }
+        }
+        next_hops
+    }
+    /// Breadth-first traversal that returns the visited nodes and the indices of the traversed
+    /// edges.
+    fn traverse(
+        &self,
+        roots: &[NodeId],
+        direction: Direction,
+        depth: Option<usize>,
+        filter: EdgeFilter,
+    ) -> (HashSet<NodeId>, HashSet<usize>) {
+        let mut adjacency: HashMap<NodeId, Vec<(NodeId, usize)>> = HashMap::new();
+        for (index, edge) in self.function_calls.iter().enumerate() {
+            if !filter.accepts(edge.kind) {
+                continue;
+            }
+            let (from, to) = match direction {
+                Direction::Forward => (edge.caller, edge.callee),
+                Direction::Backward => (edge.callee, edge.caller),
+            };
+            adjacency.entry(from).or_default().push((to, index));
+        }
+        let mut visited: HashSet<NodeId> = roots.iter().cloned().collect();
+        let mut edges = HashSet::new();
+        let mut queue: VecDeque<(NodeId, usize)> = roots.iter().map(|root| (*root, 0)).collect();
+        while let Some((node, distance)) = queue.pop_front() {
+            if depth.map_or(false, |depth| distance >= depth) {
+                continue;
+            }
+            if let Some(neighbours) = adjacency.get(&node) {
+                for (neighbour, index) in neighbours {
+                    edges.insert(*index);
+                    if visited.insert(*neighbour) {
+                        queue.push_back((*neighbour, distance + 1));
+                    }
+                }
+            }
+        }
+        (visited, edges)
+    }
+    /// Creates a call-graph that contains only the given function nodes and the call edges with
+    /// the given indices. The nodes keep their identifiers.
+    pub(crate) fn subgraph(&self, nodes: &HashSet<NodeId>, edges: &HashSet<usize>) -> CallGraph {
+        let mut subgraph = CallGraph::new();
+        for (def_path, id) in &self.node_registry {
+            if nodes.contains(id) {
+                subgraph.node_registry.insert(*def_path, *id);
+            }
+        }
+        subgraph.functions = self
+            .functions
+            .iter()
+            .filter(|node| nodes.contains(&node.id))
+            .cloned()
+            .collect();
+        let mut edges: Vec<_> = edges.iter().cloned().collect();
+        edges.sort();
+        for index in edges {
+            let edge = &self.function_calls[index];
+            subgraph.edge_registry.insert(
+                (edge.caller, edge.callee, edge.kind),
+                subgraph.function_calls.len(),
+            );
+            subgraph.function_calls.push(edge.clone());
+        }
+        subgraph
+    }
+}
+
+/// Matches a relative def path against `pattern` both as is and without disambiguators.
+pub(crate) fn def_path_matches(pattern: &str, relative_def_id: &str) -> bool {
+    glob_match(pattern, relative_def_id)
+        || glob_match(pattern, &strip_disambiguators(relative_def_id))
+}
+
+/// Removes the `[...]` disambiguators from each segment of a relative def path.
+pub(crate) fn strip_disambiguators(def_path: &str) -> String {
+    let mut result = String::with_capacity(def_path.len());
+    let mut depth = 0;
+    for c in def_path.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' if depth > 0 => depth -= 1,
+            _ if depth == 0 => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Matches `text` against a glob `pattern` that may contain `*` and `?`.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The position of the last `*` in the pattern and the text position it was tried at.
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_trailing_star() {
+        assert!(glob_match("regex::*", "regex::Regex::new"));
+        assert!(glob_match("regex::*", "regex::"));
+        assert!(!glob_match("regex::*", "regex:"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn glob_double_star() {
+        assert!(glob_match("**", ""));
+        assert!(glob_match("a**", "a"));
+        assert!(glob_match("a**c", "abbc"));
+        assert!(glob_match("a*?*c", "abc"));
+        assert!(!glob_match("a*?*c", "ac"));
+    }
+
+    #[test]
+    fn glob_empty_segments() {
+        assert!(glob_match("", ""));
+        assert!(!glob_match("", "a"));
+        assert!(!glob_match("?", ""));
+        assert!(glob_match("a::*::b", "a::::b"));
+        assert!(glob_match("a::::b", "a::::b"));
+        assert!(!glob_match("a::::b", "a::b"));
+    }
+
+    #[test]
+    fn matches_without_disambiguators() {
+        assert_eq!(
+            strip_disambiguators("regex[1a2b]::{{impl}}[3]::new[0]"),
+            "regex::{{impl}}::new"
+        );
+        assert!(def_path_matches(
+            "regex::*::new",
+            "regex[1a2b]::{{impl}}[3]::new[0]"
+        ));
+        assert!(def_path_matches(
+            "regex[1a2b]::*",
+            "regex[1a2b]::{{impl}}[3]::new[0]"
+        ));
+    }
+}
@@ -0,0 +1,330 @@
+use crate::callgraph::{CallGraph, Node, NodeId};
+use crate::query::EdgeFilter;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{self, Write};
+
+/// How far a cycle spreads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CycleScope {
+    /// A function that calls itself directly.
+    SelfRecursion,
+    /// Mutual recursion among functions of the same crate.
+    Crate,
+    /// Mutual recursion among functions of different crates of the same package.
+    Package,
+    /// Mutual recursion among functions of different packages.
+    CrossPackage,
+}
+
+#[derive(Serialize)]
+pub struct Cycle {
+    scope: CycleScope,
+    // True if the cycle exists also when only statically dispatched calls are considered.
+    is_static: bool,
+    functions: Vec<NodeId>,
+    relative_def_ids: Vec<String>,
+    packages: Vec<String>,
+    // Indices of the static cycles that a cycle, which is not static, contains.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    static_cycles: Vec<usize>,
+}
+
+/// Recursive functions found as strongly connected components of the call-graph.
+#[derive(Serialize)]
+pub struct RecursionReport {
+    num_self_recursive: usize,
+    num_crate_cycles: usize,
+    num_package_cycles: usize,
+    num_cross_package_cycles: usize,
+    // Cycles that exist only because of virtual (CHA) call edges.
+    num_virtual_only_cycles: usize,
+    cycles: Vec<Cycle>,
+}
+
+impl RecursionReport {
+    pub fn new(callgraph: &CallGraph) -> Self {
+        let nodes: HashMap<NodeId, &Node> = callgraph
+            .functions
+            .iter()
+            .map(|node| (node.id, node))
+            .collect();
+        // Every static cycle is contained in a single component of the call-graph.
+        let static_cycles = callgraph.cycles(EdgeFilter::Static);
+        let mut static_cycle = HashMap::new();
+        for (index, component) in static_cycles.iter().enumerate() {
+            for node in component {
+                static_cycle.insert(*node, index);
+            }
+        }
+        let mut report = Self {
+            num_self_recursive: 0,
+            num_crate_cycles: 0,
+            num_package_cycles: 0,
+            num_cross_package_cycles: 0,
+            num_virtual_only_cycles: 0,
+            cycles: Vec::new(),
+        };
+        for mut component in callgraph.cycles(EdgeFilter::All) {
+            component.sort();
+            let contained: BTreeSet<usize> = component
+                .iter()
+                .filter_map(|node| static_cycle.get(node).cloned())
+                .collect();
+            if contained.len() == 1 {
+                let index = *contained.iter().next().unwrap();
+                if static_cycles[index].len() == component.len() {
+                    report.add_cycle(&nodes, component, true, Vec::new());
+                    continue;
+                }
+            }
+            // The component is a cycle only because of virtual call edges, so the static cycles
+            // that it contains are reported separately.
+            let mut static_sub_cycles = Vec::new();
+            for index in contained {
+                let mut static_component = static_cycles[index].clone();
+                static_component.sort();
+                static_sub_cycles.push(report.cycles.len());
+                report.add_cycle(&nodes, static_component, true, Vec::new());
+            }
+            report.num_virtual_only_cycles += 1;
+            report.add_cycle(&nodes, component, false, static_sub_cycles);
+        }
+        report
+    }
+    fn add_cycle(
+        &mut self,
+        nodes: &HashMap<NodeId, &Node>,
+        component: Vec<NodeId>,
+        is_static: bool,
+        static_cycles: Vec<usize>,
+    ) {
+        let crates: HashSet<_> = component
+            .iter()
+            .map(|id| {
+                let node = nodes[id];
+                (&node.package_name, &node.package_version, &node.crate_name)
+            })
+            .collect();
+        let packages: BTreeSet<String> = component
+            .iter()
+            .map(|id| {
+                let node = nodes[id];
+                match (&node.package_name, &node.package_version) {
+                    (Some(name), Some(version)) => format!("{} {}", name, version),
+                    _ => String::from("unknown"),
+                }
+            })
+            .collect();
+        let scope = if component.len() == 1 {
+            self.num_self_recursive += 1;
+            CycleScope::SelfRecursion
+        } else if crates.len() == 1 {
+            self.num_crate_cycles += 1;
+            CycleScope::Crate
+        } else if packages.len() == 1 {
+            self.num_package_cycles += 1;
+            CycleScope::Package
+        } else {
+            self.num_cross_package_cycles += 1;
+            CycleScope::CrossPackage
+        };
+        let relative_def_ids = component
+            .iter()
+            .map(|id| nodes[id].relative_def_id.clone())
+            .collect();
+        self.cycles.push(Cycle {
+            scope,
+            is_static,
+            functions: component,
+            relative_def_ids,
+            packages: packages.into_iter().collect(),
+            static_cycles,
+        });
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)
+    }
+}
+
+impl CallGraph {
+    /// Returns the strongly connected components of the function call-graph that contain a
+    /// cycle, i.e., components with more than one function and self-recursive functions.
+    pub fn cycles(&self, filter: EdgeFilter) -> Vec<Vec<NodeId>> {
+        let mut self_loops = HashSet::new();
+        for edge in &self.function_calls {
+            if edge.caller == edge.callee && filter.accepts(edge.kind) {
+                self_loops.insert(edge.caller);
+            }
+        }
+        self.strongly_connected_components(filter)
+            .into_iter()
+            .filter(|component| component.len() > 1 || self_loops.contains(&component[0]))
+            .collect()
+    }
+    /// Computes the strongly connected components of the function call-graph by using an
+    /// iterative version of Tarjan's algorithm.
+    pub fn strongly_connected_components(&self, filter: EdgeFilter) -> Vec<Vec<NodeId>> {
+        let mut successors: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        for edge in &self.function_calls {
+            if filter.accepts(edge.kind) {
+                successors.entry(edge.caller).or_default().push(edge.callee);
+            }
+        }
+        let no_successors = Vec::new();
+        let mut index = HashMap::new();
+        let mut low_link = HashMap::new();
+        let mut on_stack = HashSet::new();
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        for node in &self.functions {
+            if index.contains_key(&node.id) {
+                continue;
+            }
+            // The call stack of the recursive formulation: (node, next successor to visit).
+            let mut work = vec![(node.id, 0)];
+            while let Some((current, position)) = work.pop() {
+                if position == 0 {
+                    let next_index = index.len();
+                    index.insert(current, next_index);
+                    low_link.insert(current, next_index);
+                    stack.push(current);
+                    on_stack.insert(current);
+                }
+                let current_successors = successors.get(&current).unwrap_or(&no_successors);
+                if let Some(successor) = current_successors.get(position) {
+                    work.push((current, position + 1));
+                    if !index.contains_key(successor) {
+                        work.push((*successor, 0));
+                    } else if on_stack.contains(successor) {
+                        let low = low_link[&current].min(index[successor]);
+                        low_link.insert(current, low);
+                    }
+                    continue;
+                }
+                // All successors are visited.
+                if low_link[&current] == index[&current] {
+                    let mut component = Vec::new();
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack.remove(&member);
+                        component.push(member);
+                        if member == current {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+                if let Some((parent, _)) = work.last() {
+                    let low = low_link[parent].min(low_link[&current]);
+                    low_link.insert(*parent, low);
+                }
+            }
+        }
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callgraph::EdgeKind;
+    use corpus_database::types::DefPath;
+
+    fn add_function(callgraph: &mut CallGraph, package: &str, crate_name: &str) -> NodeId {
+        let id = callgraph.functions.len();
+        let package_info = Some((package.to_string(), String::from("1.0.0")));
+        let relative_def_id = format!("{}::f{}", crate_name, id);
+        callgraph.add_node(
+            &DefPath::from(id),
+            package_info,
+            crate_name.to_string(),
+            relative_def_id,
+            true,
+            1,
+            false,
+            None,
+            None,
+        )
+    }
+
+    fn sorted_cycles(callgraph: &CallGraph, filter: EdgeFilter) -> Vec<Vec<NodeId>> {
+        let mut cycles = callgraph.cycles(filter);
+        for cycle in &mut cycles {
+            cycle.sort();
+        }
+        cycles.sort();
+        cycles
+    }
+
+    #[test]
+    fn self_loops() {
+        let mut callgraph = CallGraph::new();
+        let recursive = add_function(&mut callgraph, "a", "a");
+        let other = add_function(&mut callgraph, "a", "a");
+        callgraph.add_function_call_edge(recursive, recursive, EdgeKind::Static, None);
+        callgraph.add_function_call_edge(recursive, other, EdgeKind::Static, None);
+        // A component of a single function is a cycle only if the function calls itself.
+        assert_eq!(sorted_cycles(&callgraph, EdgeFilter::All), [[recursive]]);
+        let report = RecursionReport::new(&callgraph);
+        assert_eq!(report.num_self_recursive, 1);
+        assert_eq!(report.cycles.len(), 1);
+        assert_eq!(report.cycles[0].scope, CycleScope::SelfRecursion);
+        assert!(report.cycles[0].is_static);
+    }
+
+    #[test]
+    fn mutual_recursion_scopes() {
+        let mut callgraph = CallGraph::new();
+        let mut pairs = Vec::new();
+        for (first, second) in &[
+            (("a", "a"), ("a", "a")),
+            (("b", "b"), ("b", "b_macros")),
+            (("c", "c"), ("d", "d")),
+        ] {
+            let caller = add_function(&mut callgraph, first.0, first.1);
+            let callee = add_function(&mut callgraph, second.0, second.1);
+            callgraph.add_function_call_edge(caller, callee, EdgeKind::Static, None);
+            callgraph.add_function_call_edge(callee, caller, EdgeKind::Static, None);
+            pairs.push(vec![caller, callee]);
+        }
+        assert_eq!(sorted_cycles(&callgraph, EdgeFilter::Static), pairs);
+        let report = RecursionReport::new(&callgraph);
+        let mut scopes: Vec<_> = report
+            .cycles
+            .iter()
+            .map(|cycle| (cycle.functions.clone(), cycle.scope))
+            .collect();
+        scopes.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            scopes,
+            [
+                (pairs[0].clone(), CycleScope::Crate),
+                (pairs[1].clone(), CycleScope::Package),
+                (pairs[2].clone(), CycleScope::CrossPackage),
+            ]
+        );
+        assert_eq!(report.num_self_recursive, 0);
+    }
+
+    #[test]
+    fn virtual_only_cycle() {
+        let mut callgraph = CallGraph::new();
+        let caller = add_function(&mut callgraph, "a", "a");
+        let callee = add_function(&mut callgraph, "a", "a");
+        callgraph.add_function_call_edge(caller, callee, EdgeKind::Static, None);
+        callgraph.add_function_call_edge(callee, caller, EdgeKind::Virtual, None);
+        callgraph.add_function_call_edge(callee, callee, EdgeKind::Static, None);
+        assert_eq!(sorted_cycles(&callgraph, EdgeFilter::Static), [[callee]]);
+        let report = RecursionReport::new(&callgraph);
+        assert_eq!(report.num_virtual_only_cycles, 1);
+        assert_eq!(report.cycles.len(), 2);
+        assert_eq!(report.cycles[0].functions, [callee]);
+        assert!(report.cycles[0].is_static);
+        assert_eq!(report.cycles[1].functions, [caller, callee]);
+        assert!(!report.cycles[1].is_static);
+        assert_eq!(report.cycles[1].static_cycles, [0]);
+    }
+}
@@ -0,0 +1,1043 @@
+use corpus_database::tables::{FactValue, Tables};
+use datafrog::{Iteration, Relation, Variable};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    Number(u64),
+    Text(String),
+    Directive(String),
+    LeftParen,
+    RightParen,
+    Comma,
+    Dot,
+    Implies,
+    Not,
+    Comparison(Comparison),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Constant {
+    Number(u64),
+    Text(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Term {
+    Variable(String),
+    Constant(Constant),
+    Wildcard,
+}
+
+#[derive(Debug)]
+struct Atom {
+    relation: String,
+    terms: Vec<Term>,
+}
+
+#[derive(Debug)]
+enum Literal {
+    Positive(Atom),
+    Negative(Atom),
+    Comparison(Term, Comparison, Term),
+}
+
+#[derive(Debug)]
+struct Rule {
+    head: Atom,
+    body: Vec<Literal>,
+    line: usize,
+}
+
+/// A Datalog program, i.e., rules that derive new relations from the relations and the interning
+/// tables of the database and from each other, and the derived relations to output.
+///
+/// The syntax is a subset of Soufflé:
+/// ```text
+/// // Comment.
+/// .output reaches
+/// reaches(caller, callee) :- call_graph(_, caller, callee).
+/// reaches(caller, callee) :- reaches(caller, middle), call_graph(_, middle, callee).
+/// never_called(def_path) :- function_definitions(_, def_path, _, _, _, _, _),
+///     !call_graph(_, _, def_path).
+/// ```
+/// The body of a rule is a conjunction of atoms, negated atoms and comparisons (`=`, `!=`, `<`,
+/// `<=`, `>`, `>=`) of variables and constants. The terms are variables, `_`, numbers and
+/// strings in double quotes. A rule without a body is a fact.
+#[derive(Debug)]
+pub struct RuleProgram {
+    rules: Vec<Rule>,
+    outputs: Vec<(String, usize)>,
+}
+
+impl FromStr for RuleProgram {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+        };
+        let mut program = RuleProgram {
+            rules: Vec::new(),
+            outputs: Vec::new(),
+        };
+        while parser.position < parser.tokens.len() {
+            let line = parser.line();
+            match parser.next()? {
+                Token::Directive(ref directive) if directive == "output" => {
+                    program.outputs.push((parser.identifier()?, line));
+                    while parser.peek() == Some(&Token::Comma) {
+                        parser.next()?;
+                        program.outputs.push((parser.identifier()?, line));
+                    }
+                }
+                Token::Directive(directive) => {
+                    return Err(format!("line {}: unknown directive `.{}`", line, directive));
+                }
+                Token::Identifier(relation) => {
+                    let head = parser.atom(relation)?;
+                    let mut body = Vec::new();
+                    if parser.peek() == Some(&Token::Implies) {
+                        parser.next()?;
+                        body.push(parser.literal()?);
+                        while parser.peek() == Some(&Token::Comma) {
+                            parser.next()?;
+                            body.push(parser.literal()?);
+                        }
+                    }
+                    parser.expect(Token::Dot)?;
+                    program.rules.push(Rule { head, body, line });
+                }
+                token => return Err(format!("line {}: unexpected {:?}", line, token)),
+            }
+        }
+        Ok(program)
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let next = chars.get(i + 1).cloned();
+        let (token, length) = match chars[i] {
+            '\n' => {
+                line += 1;
+                i += 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '/' if next == Some('/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '(' => (Token::LeftParen, 1),
+            ')' => (Token::RightParen, 1),
+            ',' => (Token::Comma, 1),
+            ':' if next == Some('-') => (Token::Implies, 2),
+            '!' if next == Some('=') => (Token::Comparison(Comparison::NotEqual), 2),
+            '!' => (Token::Not, 1),
+            '=' => (Token::Comparison(Comparison::Equal), 1),
+            '<' if next == Some('=') => (Token::Comparison(Comparison::LessOrEqual), 2),
+            '<' => (Token::Comparison(Comparison::Less), 1),
+            '>' if next == Some('=') => (Token::Comparison(Comparison::GreaterOrEqual), 2),
+            '>' => (Token::Comparison(Comparison::Greater), 1),
+            // A directive starts a clause, i.e., it is the first token of a line or follows the
+            // `.` that ends the previous clause. Otherwise, `a(1).b(2).` would be a directive.
+            '.' if next.map_or(false, char::is_alphabetic)
+                && tokens.last().map_or(true, |(token, token_line)| {
+                    *token == Token::Dot || *token_line < line
+                }) =>
+            {
+                let name = take_while(&chars[i + 1..], |c| c.is_alphanumeric() || c == '_');
+                let length = name.len() + 1;
+                (Token::Directive(name), length)
+            }
+            '.' => (Token::Dot, 1),
+            '"' => {
+                let mut value = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        Some('"') => break,
+                        Some('\\') => {
+                            match chars.get(j + 1) {
+                                Some('n') => value.push('\n'),
+                                Some('t') => value.push('\t'),
+                                Some(c) => value.push(*c),
+                                None => return Err(format!("line {}: unterminated string", line)),
+                            }
+                            j += 1;
+                        }
+                        Some('\n') | None => {
+                            return Err(format!("line {}: unterminated string", line));
+                        }
+                        Some(c) => value.push(*c),
+                    }
+                    j += 1;
+                }
+                (Token::Text(value), j + 1 - i)
+            }
+            c if c.is_ascii_digit() => {
+                let digits = take_while(&chars[i..], |c| c.is_ascii_digit());
+                let number = digits
+                    .parse()
+                    .map_err(|_| format!("line {}: invalid number {}", line, digits))?;
+                (Token::Number(number), digits.len())
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let name = take_while(&chars[i..], |c| c.is_alphanumeric() || c == '_');
+                let length = name.len();
+                (Token::Identifier(name), length)
+            }
+            c => return Err(format!("line {}: unexpected character `{}`", line, c)),
+        };
+        tokens.push((token, line));
+        i += length;
+    }
+    Ok(tokens)
+}
+
+fn take_while(chars: &[char], predicate: impl Fn(char) -> bool) -> String {
+    chars
+        .iter()
+        .cloned()
+        .take_while(|c| predicate(*c))
+        .collect()
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        match self
+            .tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+        {
+            Some((_, line)) => *line,
+            None => 1,
+        }
+    }
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+    fn next(&mut self) -> Result<Token, String> {
+        match self.tokens.get(self.position) {
+            Some((token, _)) => {
+                self.position += 1;
+                Ok(token.clone())
+            }
+            None => Err(format!("line {}: unexpected end of the rules", self.line())),
+        }
+    }
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        let line = self.line();
+        match self.next()? {
+            ref token if *token == expected => Ok(()),
+            token => Err(format!(
+                "line {}: expected {:?}, found {:?}",
+                line, expected, token
+            )),
+        }
+    }
+    fn identifier(&mut self) -> Result<String, String> {
+        let line = self.line();
+        match self.next()? {
+            Token::Identifier(name) => Ok(name),
+            token => Err(format!("line {}: expected a name, found {:?}", line, token)),
+        }
+    }
+    fn atom(&mut self, relation: String) -> Result<Atom, String> {
+        self.expect(Token::LeftParen)?;
+        let mut terms = Vec::new();
+        if self.peek() != Some(&Token::RightParen) {
+            terms.push(self.term()?);
+            while self.peek() == Some(&Token::Comma) {
+                self.next()?;
+                terms.push(self.term()?);
+            }
+        }
+        self.expect(Token::RightParen)?;
+        Ok(Atom { relation, terms })
+    }
+    fn term(&mut self) -> Result<Term, String> {
+        let line = self.line();
+        match self.next()? {
+            Token::Identifier(ref name) if name == "_" => Ok(Term::Wildcard),
+            Token::Identifier(name) => Ok(Term::Variable(name)),
+            Token::Number(number) => Ok(Term::Constant(Constant::Number(number))),
+            Token::Text(text) => Ok(Term::Constant(Constant::Text(text))),
+            token => Err(format!("line {}: expected a term, found {:?}", line, token)),
+        }
+    }
+    fn literal(&mut self) -> Result<Literal, String> {
+        let line = self.line();
+        if self.peek() == Some(&Token::Not) {
+            self.next()?;
+            let relation = self.identifier()?;
+            return Ok(Literal::Negative(self.atom(relation)?));
+        }
+        if let (Some(Token::Identifier(_)), Some((Token::LeftParen, _))) =
+            (self.peek(), self.tokens.get(self.position + 1))
+        {
+            let relation = self.identifier()?;
+            return Ok(Literal::Positive(self.atom(relation)?));
+        }
+        let left = self.term()?;
+        match self.next()? {
+            Token::Comparison(comparison) => {
+                Ok(Literal::Comparison(left, comparison, self.term()?))
+            }
+            token => Err(format!(
+                "line {}: expected an atom or a comparison, found {:?}",
+                line, token
+            )),
+        }
+    }
+}
+
+/// A value of a column. The strings are interned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Value {
+    Number(u64),
+    Symbol(usize),
+}
+
+type Tuple = Vec<Value>;
+
+#[derive(Default)]
+struct Symbols {
+    indices: HashMap<String, usize>,
+    strings: Vec<String>,
+}
+
+impl Symbols {
+    fn intern(&mut self, string: &str) -> Value {
+        if let Some(index) = self.indices.get(string) {
+            return Value::Symbol(*index);
+        }
+        let index = self.strings.len();
+        self.strings.push(string.to_string());
+        self.indices.insert(string.to_string(), index);
+        Value::Symbol(index)
+    }
+}
+
+/// A term of a rule in which the variables are replaced with their positions in the bindings.
+#[derive(Clone, Copy)]
+enum CompiledTerm {
+    Variable(usize),
+    Constant(Value),
+    Wildcard,
+}
+
+/// Matches the tuples of a relation against the terms of an atom: checks the constants and the
+/// repeated variables and extracts the values of the `key` and the `values` columns.
+#[derive(Clone)]
+struct Pattern {
+    constants: Vec<(usize, Value)>,
+    equalities: Vec<(usize, usize)>,
+    key: Vec<usize>,
+    values: Vec<usize>,
+}
+
+impl Pattern {
+    /// Creates a pattern whose key are the variables bound by the previous atoms (the ones with
+    /// positions less than `bound`), ordered by their positions, and whose values are the
+    /// variables that the atom binds.
+    fn new(terms: &[CompiledTerm], bound: usize) -> Self {
+        let mut pattern = Pattern {
+            constants: Vec::new(),
+            equalities: Vec::new(),
+            key: Vec::new(),
+            values: Vec::new(),
+        };
+        let mut first_columns: BTreeMap<usize, usize> = BTreeMap::new();
+        for (column, term) in terms.iter().enumerate() {
+            match term {
+                CompiledTerm::Variable(variable) => match first_columns.get(variable) {
+                    Some(first) => pattern.equalities.push((*first, column)),
+                    None => {
+                        first_columns.insert(*variable, column);
+                    }
+                },
+                CompiledTerm::Constant(value) => pattern.constants.push((column, *value)),
+                CompiledTerm::Wildcard => {}
+            }
+        }
+        for (variable, column) in first_columns {
+            if variable < bound {
+                pattern.key.push(column);
+            } else {
+                pattern.values.push(column);
+            }
+        }
+        pattern
+    }
+    fn apply(&self, tuple: &[Value]) -> Option<(Tuple, Tuple)> {
+        let matches = self
+            .constants
+            .iter()
+            .all(|(column, value)| tuple[*column] == *value)
+            && self
+                .equalities
+                .iter()
+                .all(|(first, second)| tuple[*first] == tuple[*second]);
+        if !matches {
+            return None;
+        }
+        let key = self.key.iter().map(|column| tuple[*column]).collect();
+        let values = self.values.iter().map(|column| tuple[*column]).collect();
+        Some((key, values))
+    }
+}
+
+/// The source of the tuples of an atom: a relation that is derived in the current stratum or a
+/// relation that is already complete.
+enum Source {
+    Variable(Variable<Tuple>),
+    Relation(Rc<Relation<Tuple>>),
+}
+
+/// A step of the evaluation of a rule that is executed once per round.
+type Step = Box<dyn Fn()>;
+
+/// Adds the tuples that result from mapping the recent tuples of `input` and that are not
+/// `None`.
+fn filter_map_into<T1: Ord, T2: Ord>(
+    input: &Variable<T1>,
+    output: &Variable<T2>,
+    logic: impl Fn(&T1) -> Option<T2>,
+) {
+    let results: Vec<_> = input.recent.borrow().iter().filter_map(logic).collect();
+    output.insert(results.into());
+}
+
+/// The relations derived by a rule program from the relations and the interning tables of a
+/// database.
+pub struct DerivedRelations {
+    relations: HashMap<String, Rc<Relation<Tuple>>>,
+    columns: HashMap<String, Vec<String>>,
+    outputs: Vec<String>,
+    symbols: Rc<Symbols>,
+}
+
+impl DerivedRelations {
+    /// Evaluates the rules of `program` stratum by stratum. Every relation that is used in a
+    /// rule, but is not derived by the program, is loaded from `tables`.
+    pub fn new(tables: &Tables, program: &RuleProgram) -> Result<Self, String> {
+        let mut arities = HashMap::new();
+        let mut columns = HashMap::new();
+        for rule in &program.rules {
+            let names = rule
+                .head
+                .terms
+                .iter()
+                .enumerate()
+                .map(|(i, term)| match term {
+                    Term::Variable(name) => name.clone(),
+                    _ => format!("column{}", i),
+                })
+                .collect();
+            columns.entry(rule.head.relation.clone()).or_insert(names);
+        }
+        let mut inputs = HashSet::new();
+        for rule in &program.rules {
+            check_rule(rule)?;
+            let atoms = rule.body.iter().filter_map(|literal| match literal {
+                Literal::Positive(atom) | Literal::Negative(atom) => Some(atom),
+                Literal::Comparison(..) => None,
+            });
+            for atom in std::iter::once(&rule.head).chain(atoms) {
+                let arity = match Tables::fact_columns(&atom.relation) {
+                    _ if columns.contains_key(&atom.relation) => {
+                        *arities.entry(&atom.relation).or_insert(atom.terms.len())
+                    }
+                    Some(input_columns) => {
+                        inputs.insert(atom.relation.clone());
+                        input_columns.len()
+                    }
+                    None => {
+                        return Err(format!(
+                            "line {}: unknown relation `{}`",
+                            rule.line, atom.relation
+                        ));
+                    }
+                };
+                if arity != atom.terms.len() {
+                    return Err(format!(
+                        "line {}: `{}` has {} columns, but is used with {}",
+                        rule.line,
+                        atom.relation,
+                        arity,
+                        atom.terms.len()
+                    ));
+                }
+            }
+        }
+        for (output, line) in &program.outputs {
+            if !columns.contains_key(output) {
+                return Err(format!(
+                    "line {}: `{}` is not derived by any rule",
+                    line, output
+                ));
+            }
+        }
+        let strata = stratify(program)?;
+        let mut symbols = Symbols::default();
+        let mut relations = HashMap::new();
+        for input in inputs {
+            let mut tuples = Vec::new();
+            tables.visit_facts(&input, |values| {
+                let tuple = values
+                    .iter()
+                    .map(|value| match value {
+                        FactValue::Number(number) => Value::Number(*number),
+                        FactValue::Text(text) => symbols.intern(text),
+                    })
+                    .collect();
+                tuples.push(tuple);
+            });
+            relations.insert(input, Rc::new(Relation::from_vec(tuples)));
+        }
+        let rules: Vec<_> = program
+            .rules
+            .iter()
+            .map(|rule| compile_rule(rule, &mut symbols))
+            .collect();
+        let symbols = Rc::new(symbols);
+        for stratum in strata {
+            let mut iteration = Iteration::new();
+            let variables: HashMap<&str, Variable<Tuple>> = stratum
+                .iter()
+                .map(|relation| (relation.as_str(), iteration.variable(relation)))
+                .collect();
+            let mut steps = Vec::new();
+            for rule in &rules {
+                if let Some(head) = variables.get(rule.head.as_str()) {
+                    let sources = |relation: &str| match variables.get(relation) {
+                        Some(variable) => Source::Variable(variable.clone()),
+                        None => Source::Relation(relations[relation].clone()),
+                    };
+                    steps.extend(rule.plan(&mut iteration, head, sources, &relations, &symbols));
+                }
+            }
+            while iteration.changed() {
+                for step in &steps {
+                    step();
+                }
+            }
+            drop(steps);
+            for (relation, variable) in variables {
+                relations.insert(relation.to_string(), Rc::new(variable.complete()));
+            }
+        }
+        relations.retain(|relation, _| columns.contains_key(relation));
+        Ok(Self {
+            relations,
+            columns,
+            outputs: program
+                .outputs
+                .iter()
+                .map(|(output, _)| output.clone())
+                .collect(),
+            symbols,
+        })
+    }
+    /// The relations marked with `.output`.
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+    /// Returns the column names of a derived relation, which are the names of the variables
+    /// in the head of its first rule.
+    pub fn columns(&self, relation: &str) -> Option<&[String]> {
+        self.columns.get(relation).map(|columns| columns.as_slice())
+    }
+    /// Returns the facts of a derived relation with the same values as the ones of the
+    /// relations in the database.
+    pub fn facts(&self, relation: &str) -> Option<Vec<Vec<FactValue<'_>>>> {
+        let relation = self.relations.get(relation)?;
+        let facts = relation
+            .iter()
+            .map(|tuple| {
+                tuple
+                    .iter()
+                    .map(|value| match value {
+                        Value::Number(number) => FactValue::Number(*number),
+                        Value::Symbol(index) => {
+                            FactValue::Text(Cow::Borrowed(&self.symbols.strings[*index]))
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Some(facts)
+    }
+    /// Writes a derived relation as CSV with a header row.
+    pub fn write_csv<W: Write>(&self, relation: &str, writer: &mut W) -> io::Result<()> {
+        let columns = &self.columns[relation];
+        writeln!(writer, "{}", columns.join(","))?;
+        for tuple in self.relations[relation].iter() {
+            let values: Vec<Cow<str>> = tuple
+                .iter()
+                .map(|value| match value {
+                    Value::Number(number) => Cow::Owned(number.to_string()),
+                    Value::Symbol(index) => csv_field(&self.symbols.strings[*index]),
+                })
+                .collect();
+            writeln!(writer, "{}", values.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Checks that every variable of the head, the negated atoms and the comparisons of a rule is
+/// bound by a positive atom.
+fn check_rule(rule: &Rule) -> Result<(), String> {
+    let mut bound = HashSet::new();
+    for literal in &rule.body {
+        if let Literal::Positive(atom) = literal {
+            for term in &atom.terms {
+                if let Term::Variable(name) = term {
+                    bound.insert(name.as_str());
+                }
+            }
+        }
+    }
+    let check = |term: &Term| match term {
+        Term::Variable(name) if !bound.contains(name.as_str()) => Err(format!(
+            "line {}: variable `{}` is not bound by a positive atom",
+            rule.line, name
+        )),
+        _ => Ok(()),
+    };
+    for term in &rule.head.terms {
+        if *term == Term::Wildcard {
+            return Err(format!("line {}: `_` in the head of a rule", rule.line));
+        }
+        check(term)?;
+    }
+    for literal in &rule.body {
+        match literal {
+            Literal::Negative(atom) => atom.terms.iter().map(check).collect::<Result<_, _>>()?,
+            Literal::Comparison(left, _, right) => {
+                check(left)?;
+                check(right)?;
+            }
+            Literal::Positive(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Assigns every derived relation to a stratum such that a relation is in the same or a later
+/// stratum than the relations it uses, and in a later stratum than the relations it negates.
+/// Returns the relations of each stratum in the order of evaluation.
+fn stratify(program: &RuleProgram) -> Result<Vec<Vec<String>>, String> {
+    let mut strata: BTreeMap<&str, usize> = program
+        .rules
+        .iter()
+        .map(|rule| (rule.head.relation.as_str(), 0))
+        .collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for rule in &program.rules {
+            for literal in &rule.body {
+                let (atom, offset) = match literal {
+                    Literal::Positive(atom) => (atom, 0),
+                    Literal::Negative(atom) => (atom, 1),
+                    Literal::Comparison(..) => continue,
+                };
+                let stratum = match strata.get(atom.relation.as_str()) {
+                    Some(stratum) => stratum + offset,
+                    None => continue,
+                };
+                if strata[rule.head.relation.as_str()] < stratum {
+                    if stratum > strata.len() {
+                        return Err(format!(
+                            "line {}: `{}` depends on the negation of `{}` recursively",
+                            rule.line, rule.head.relation, atom.relation
+                        ));
+                    }
+                    strata.insert(&rule.head.relation, stratum);
+                    changed = true;
+                }
+            }
+        }
+    }
+    let mut result: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for (relation, stratum) in strata {
+        result
+            .entry(stratum)
+            .or_default()
+            .push(relation.to_string());
+    }
+    Ok(result.into_iter().map(|(_, relations)| relations).collect())
+}
+
+/// A rule in which the variables are numbered in the order in which the positive atoms bind
+/// them, so that the bindings after each atom are a prefix of the bindings of the next atom.
+struct CompiledRule {
+    head: String,
+    head_terms: Vec<CompiledTerm>,
+    positive: Vec<(String, Vec<CompiledTerm>)>,
+    negative: Vec<(String, Vec<CompiledTerm>)>,
+    comparisons: Vec<(CompiledTerm, Comparison, CompiledTerm)>,
+}
+
+fn compile_rule(rule: &Rule, symbols: &mut Symbols) -> CompiledRule {
+    let mut variables = HashMap::new();
+    for literal in &rule.body {
+        if let Literal::Positive(atom) = literal {
+            for term in &atom.terms {
+                if let Term::Variable(name) = term {
+                    let next = variables.len();
+                    variables.entry(name.clone()).or_insert(next);
+                }
+            }
+        }
+    }
+    let mut compile = |term: &Term| match term {
+        Term::Variable(name) => CompiledTerm::Variable(variables[name]),
+        Term::Constant(Constant::Number(number)) => CompiledTerm::Constant(Value::Number(*number)),
+        Term::Constant(Constant::Text(text)) => CompiledTerm::Constant(symbols.intern(text)),
+        Term::Wildcard => CompiledTerm::Wildcard,
+    };
+    let mut compiled = CompiledRule {
+        head: rule.head.relation.clone(),
+        head_terms: rule.head.terms.iter().map(&mut compile).collect(),
+        positive: Vec::new(),
+        negative: Vec::new(),
+        comparisons: Vec::new(),
+    };
+    for literal in &rule.body {
+        match literal {
+            Literal::Positive(atom) => compiled.positive.push((
+                atom.relation.clone(),
+                atom.terms.iter().map(&mut compile).collect(),
+            )),
+            Literal::Negative(atom) => compiled.negative.push((
+                atom.relation.clone(),
+                atom.terms.iter().map(&mut compile).collect(),
+            )),
+            Literal::Comparison(left, comparison, right) => {
+                compiled
+                    .comparisons
+                    .push((compile(left), *comparison, compile(right)))
+            }
+        }
+    }
+    compiled
+}
+
+impl CompiledRule {
+    /// Creates the steps that evaluate the rule in every round of `iteration`: the positive
+    /// atoms are joined from left to right, then the negated atoms are removed and finally the
+    /// comparisons are checked and the head is added to `head`.
+    fn plan(
+        &self,
+        iteration: &mut Iteration,
+        head: &Variable<Tuple>,
+        sources: impl Fn(&str) -> Source,
+        relations: &HashMap<String, Rc<Relation<Tuple>>>,
+        symbols: &Rc<Symbols>,
+    ) -> Vec<Step> {
+        let mut steps: Vec<Step> = Vec::new();
+        let head_terms = self.head_terms.clone();
+        let tuple = move |bindings: &[Value]| -> Tuple {
+            head_terms
+                .iter()
+                .map(|term| match term {
+                    CompiledTerm::Variable(variable) => bindings[*variable],
+                    CompiledTerm::Constant(value) => *value,
+                    CompiledTerm::Wildcard => unreachable!(),
+                })
+                .collect()
+        };
+        if self.positive.is_empty() {
+            // A fact.
+            head.insert(Relation::from_vec(vec![tuple(&[])]));
+            return steps;
+        }
+        let mut bound = 0;
+        let mut bindings: Variable<Tuple> = iteration.variable("bindings");
+        for (i, (relation, terms)) in self.positive.iter().enumerate() {
+            let pattern = Pattern::new(terms, bound);
+            bound += pattern.values.len();
+            if i == 0 {
+                let logic = move |tuple: &Tuple| pattern.apply(tuple).map(|(_, values)| values);
+                match sources(relation) {
+                    Source::Variable(variable) => {
+                        let bindings = bindings.clone();
+                        steps.push(Box::new(move || {
+                            filter_map_into(&variable, &bindings, &logic)
+                        }));
+                    }
+                    Source::Relation(relation) => {
+                        bindings.insert(relation.iter().filter_map(logic).collect());
+                    }
+                }
+                continue;
+            }
+            let left: Variable<(Tuple, Tuple)> = iteration.variable("left");
+            let key = pattern.key.clone();
+            let key_variables: Vec<usize> = key
+                .iter()
+                .map(|column| match terms[*column] {
+                    CompiledTerm::Variable(variable) => variable,
+                    _ => unreachable!(),
+                })
+                .collect();
+            let previous = bindings.clone();
+            let left_step = left.clone();
+            steps.push(Box::new(move || {
+                left_step.from_map(&previous, |bindings| {
+                    let key = key_variables.iter().map(|v| bindings[*v]).collect();
+                    (key, bindings.clone())
+                })
+            }));
+            let joined: Variable<Tuple> = iteration.variable("bindings");
+            let join = |_: &Tuple, left: &Tuple, right: &Tuple| {
+                let mut bindings = left.clone();
+                bindings.extend(right);
+                bindings
+            };
+            match sources(relation) {
+                Source::Variable(variable) => {
+                    let right: Variable<(Tuple, Tuple)> = iteration.variable("right");
+                    let joined = joined.clone();
+                    steps.push(Box::new(move || {
+                        filter_map_into(&variable, &right, |tuple| pattern.apply(tuple));
+                        joined.from_join(&left, &right, join);
+                    }));
+                }
+                Source::Relation(relation) => {
+                    let right: Relation<(Tuple, Tuple)> = relation
+                        .iter()
+                        .filter_map(|tuple| pattern.apply(tuple))
+                        .collect();
+                    let joined = joined.clone();
+                    steps.push(Box::new(move || joined.from_join(&left, &right, join)));
+                }
+            }
+            bindings = joined;
+        }
+        for (relation, terms) in &self.negative {
+            // Negated atoms bind no new variables, so all their variables form the key.
+            let pattern = Pattern::new(terms, bound);
+            let negated: Relation<Tuple> = relations[relation]
+                .iter()
+                .filter_map(|tuple| pattern.apply(tuple).map(|(key, _)| key))
+                .collect();
+            let key_variables: Vec<usize> = pattern
+                .key
+                .iter()
+                .map(|column| match terms[*column] {
+                    CompiledTerm::Variable(variable) => variable,
+                    _ => unreachable!(),
+                })
+                .collect();
+            let left: Variable<(Tuple, Tuple)> = iteration.variable("left");
+            let remaining: Variable<Tuple> = iteration.variable("bindings");
+            let previous = bindings.clone();
+            let result = remaining.clone();
+            steps.push(Box::new(move || {
+                left.from_map(&previous, |bindings| {
+                    let key = key_variables.iter().map(|v| bindings[*v]).collect();
+                    (key, bindings.clone())
+                });
+                result.from_antijoin(&left, &negated, |_, bindings| bindings.clone());
+            }));
+            bindings = remaining;
+        }
+        let comparisons = self.comparisons.clone();
+        let symbols = symbols.clone();
+        let head = head.clone();
+        steps.push(Box::new(move || {
+            filter_map_into(&bindings, &head, |bindings| {
+                let holds = comparisons.iter().all(|(left, comparison, right)| {
+                    compare(
+                        resolve(*left, bindings),
+                        *comparison,
+                        resolve(*right, bindings),
+                        &symbols,
+                    )
+                });
+                if holds {
+                    Some(tuple(bindings))
+                } else {
+                    None
+                }
+            })
+        }));
+        steps
+    }
+}
+
+fn resolve(term: CompiledTerm, bindings: &[Value]) -> Value {
+    match term {
+        CompiledTerm::Variable(variable) => bindings[variable],
+        CompiledTerm::Constant(value) => value,
+        CompiledTerm::Wildcard => unreachable!(),
+    }
+}
+
+/// Compares numbers by their values and strings in lexicographic order. A number is less than
+/// any string.
+fn compare(left: Value, comparison: Comparison, right: Value, symbols: &Symbols) -> bool {
+    let ordering = match (left, right) {
+        (Value::Symbol(left), Value::Symbol(right)) => {
+            symbols.strings[left].cmp(&symbols.strings[right])
+        }
+        (left, right) => left.cmp(&right),
+    };
+    match comparison {
+        Comparison::Equal => ordering == Ordering::Equal,
+        Comparison::NotEqual => ordering != Ordering::Equal,
+        Comparison::Less => ordering == Ordering::Less,
+        Comparison::LessOrEqual => ordering != Ordering::Greater,
+        Comparison::Greater => ordering == Ordering::Greater,
+        Comparison::GreaterOrEqual => ordering != Ordering::Less,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derive(text: &str) -> Result<DerivedRelations, String> {
+        let program: RuleProgram = text.parse()?;
+        DerivedRelations::new(&Tables::default(), &program)
+    }
+
+    fn csv(relations: &DerivedRelations, relation: &str) -> String {
+        let mut output = Vec::new();
+        relations.write_csv(relation, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn tokenize_escapes() {
+        let tokens: Vec<Token> = tokenize(r#""a\"b\\c\nd\te""#)
+            .unwrap()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect();
+        assert_eq!(tokens, vec![Token::Text(String::from("a\"b\\c\nd\te"))]);
+        assert!(tokenize("\"a\\").is_err());
+        assert!(tokenize("\"a\nb\"").is_err());
+    }
+
+    #[test]
+    fn clauses_without_whitespace() {
+        let relations = derive(".output a, b\n.output c\na(1).b(2).c(x) :- a(x).").unwrap();
+        assert_eq!(relations.outputs(), ["a", "b", "c"]);
+        assert_eq!(csv(&relations, "b"), "column0\n2\n");
+        assert_eq!(csv(&relations, "c"), "x\n1\n");
+        let relations = derive("a(1). .output a").unwrap();
+        assert_eq!(relations.outputs(), ["a"]);
+    }
+
+    #[test]
+    fn recursive_negation() {
+        let error = derive("p(x) :- q(x), !p(x).\nq(1).").err().unwrap();
+        assert_eq!(
+            error,
+            "line 1: `p` depends on the negation of `p` recursively"
+        );
+        let error = derive("p(x) :- q(x), !r(x).\nr(x) :- q(x), !p(x).\nq(1).")
+            .err()
+            .unwrap();
+        assert!(error.contains("depends on the negation of"), "{}", error);
+    }
+
+    #[test]
+    fn pattern_repeated_variables_and_constants() {
+        let terms = [
+            CompiledTerm::Variable(0),
+            CompiledTerm::Constant(Value::Number(7)),
+            CompiledTerm::Variable(1),
+            CompiledTerm::Variable(0),
+            CompiledTerm::Wildcard,
+        ];
+        let pattern = Pattern::new(&terms, 1);
+        let tuple =
+            |values: &[u64]| -> Tuple { values.iter().cloned().map(Value::Number).collect() };
+        assert_eq!(
+            pattern.apply(&tuple(&[1, 7, 2, 1, 9])),
+            Some((tuple(&[1]), tuple(&[2])))
+        );
+        assert_eq!(pattern.apply(&tuple(&[1, 8, 2, 1, 9])), None);
+        assert_eq!(pattern.apply(&tuple(&[1, 7, 2, 3, 9])), None);
+    }
+
+    #[test]
+    fn transitive_closure() {
+        let relations = derive(
+            r#"
+            .output reaches
+            edge(1, 2). edge(2, 3). edge(3, 1). edge(4, 5).
+            reaches(x, y) :- edge(x, y).
+            reaches(x, z) :- reaches(x, y), edge(y, z).
+            "#,
+        )
+        .unwrap();
+        assert_eq!(relations.outputs(), ["reaches"]);
+        assert_eq!(
+            csv(&relations, "reaches"),
+            "x,y\n1,1\n1,2\n1,3\n2,1\n2,2\n2,3\n3,1\n3,2\n3,3\n4,5\n"
+        );
+    }
+
+    #[test]
+    fn negation() {
+        let relations = derive(
+            r#"
+            edge(1, 2). edge(2, 3). edge(4, 5).
+            node(x) :- edge(x, _).
+            node(y) :- edge(_, y).
+            reaches(x, y) :- edge(x, y).
+            reaches(x, z) :- reaches(x, y), edge(y, z).
+            unreached(x) :- node(x), !reaches(1, x), x != 1.
+            "#,
+        )
+        .unwrap();
+        assert_eq!(csv(&relations, "unreached"), "x\n4\n5\n");
+    }
+}
@@ -0,0 +1,327 @@
+use crate::callgraph::{CallGraph, Edge, Node, NodeId};
+use crate::export::OutputFormat;
+use crate::metrics::{Metrics, MetricsLevel};
+use crate::query::{Direction, EdgeFilter};
+use crate::types::TypeHierarchy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+const ENDPOINTS: &[&str] = &[
+    "GET /nodes?pattern=<pattern>",
+    "GET /nodes/<id>",
+    "GET /neighbors?id=<id>[&direction=forward|backward][&edges=<edges>]",
+    "GET /reachable?pattern=<pattern>|id=<id>[&direction=forward|backward][&depth=<depth>][&edges=<edges>]",
+    "GET /impls?trait=<pattern>",
+    "GET /packages",
+];
+
+// The maximum size of the request line and the headers together, in bytes.
+const MAX_REQUEST_SIZE: u64 = 8 * 1024;
+// The time after which reading a request or writing a response is given up.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize)]
+struct Neighbor<'a> {
+    edge: &'a Edge,
+    node: &'a Node,
+}
+
+#[derive(Serialize)]
+struct Neighbors<'a> {
+    node: &'a Node,
+    neighbors: Vec<Neighbor<'a>>,
+}
+
+#[derive(Serialize)]
+struct Error {
+    error: String,
+}
+
+// The HTTP status code and the JSON body of a response.
+type Response = (u16, Vec<u8>);
+
+/// Answers JSON-over-HTTP queries about a call-graph and a type-hierarchy that are kept in
+/// memory. Connections are handled by a fixed number of worker threads.
+pub struct QueryServer<'a> {
+    callgraph: &'a CallGraph,
+    types: &'a TypeHierarchy,
+    metrics: Metrics,
+    nodes: HashMap<NodeId, &'a Node>,
+}
+
+impl<'a> QueryServer<'a> {
+    pub fn new(callgraph: &'a CallGraph, types: &'a TypeHierarchy) -> Self {
+        let nodes = callgraph
+            .functions
+            .iter()
+            .chain(callgraph.macros.iter())
+            .map(|node| (node.id, node))
+            .collect();
+        Self {
+            callgraph,
+            types,
+            metrics: Metrics::new(callgraph),
+            nodes,
+        }
+    }
+    /// Listens on `address` and answers the requests on `num_workers` threads until the process
+    /// is terminated.
+    pub fn serve<A: ToSocketAddrs>(&self, address: A, num_workers: usize) -> io::Result<()> {
+        let listener = TcpListener::bind(address)?;
+        let (sender, receiver) = mpsc::channel::<TcpStream>();
+        let receiver = Mutex::new(receiver);
+        crossbeam_utils::thread::scope(|scope| {
+            for _ in 0..num_workers.max(1) {
+                scope.spawn(|_| loop {
+                    let stream = receiver.lock().unwrap().recv();
+                    match stream {
+                        Ok(stream) => {
+                            if let Err(error) = self.handle_connection(stream) {
+                                eprintln!("Failed to handle a request: {}", error);
+                            }
+                        }
+                        // The listener is closed.
+                        Err(_) => break,
+                    }
+                });
+            }
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => sender.send(stream).unwrap(),
+                    Err(error) => eprintln!("Failed to accept a connection: {}", error),
+                }
+            }
+            drop(sender);
+        })
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "A worker thread panicked."))
+    }
+    fn handle_connection(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?.take(MAX_REQUEST_SIZE));
+        let mut request_line = Vec::new();
+        let mut is_complete = read_line(&mut reader, &mut request_line)?;
+        // Skip the headers; requests have no body.
+        let mut header = Vec::new();
+        while is_complete {
+            header.clear();
+            is_complete = read_line(&mut reader, &mut header)?;
+            if header.iter().all(u8::is_ascii_whitespace) {
+                break;
+            }
+        }
+        let (status, body) = match std::str::from_utf8(&request_line) {
+            _ if !is_complete => error(400, "Incomplete or too large request."),
+            Ok(request_line) => {
+                let mut tokens = request_line.split_whitespace();
+                match (tokens.next(), tokens.next()) {
+                    (Some("GET"), Some(target)) => self.handle(target),
+                    (Some(_), Some(_)) => error(405, "Only GET requests are supported."),
+                    _ => error(400, "Malformed request."),
+                }
+            }
+            Err(_) => error(400, "Malformed request."),
+        };
+        let reason = match status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            _ => "Method Not Allowed",
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n",
+            status,
+            reason,
+            body.len()
+        )?;
+        stream.write_all(&body)?;
+        stream.flush()
+    }
+    /// Answers a request for the given target, i.e., a path with an optional query string.
+    pub fn handle(&self, target: &str) -> Response {
+        let (path, query) = match target.find('?') {
+            Some(index) => (&target[..index], &target[index + 1..]),
+            None => (target, ""),
+        };
+        let parameters: HashMap<String, String> = query
+            .split('&')
+            .filter(|parameter| !parameter.is_empty())
+            .map(|parameter| match parameter.find('=') {
+                Some(index) => (
+                    percent_decode(&parameter[..index]),
+                    percent_decode(&parameter[index + 1..]),
+                ),
+                None => (percent_decode(parameter), String::new()),
+            })
+            .collect();
+        match self.route(path, &parameters) {
+            Ok(response) => response,
+            Err(message) => error(400, &message),
+        }
+    }
+    fn route(&self, path: &str, parameters: &HashMap<String, String>) -> Result<Response, String> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let response = match segments.as_slice() {
+            [""] => json(&ENDPOINTS),
+            ["nodes"] => {
+                let pattern = required(parameters, "pattern")?;
+                let nodes: Vec<&Node> = self
+                    .callgraph
+                    .find_functions(pattern)
+                    .iter()
+                    .map(|id| self.nodes[id])
+                    .collect();
+                json(&nodes)
+            }
+            ["nodes", id] => match self.nodes.get(&parse(id, "id")?) {
+                Some(node) => json(node),
+                None => error(404, &format!("No node with id {}", id)),
+            },
+            ["neighbors"] => {
+                let id: NodeId = parse(required(parameters, "id")?, "id")?;
+                let node = match self.nodes.get(&id) {
+                    Some(node) => node,
+                    None => return Ok(error(404, &format!("No node with id {}", id))),
+                };
+                let direction = direction(parameters)?;
+                let filter = edge_filter(parameters)?;
+                let neighbors = self
+                    .callgraph
+                    .function_calls
+                    .iter()
+                    .filter(|edge| filter.accepts(edge.kind))
+                    .filter_map(|edge| match direction {
+                        Direction::Forward if edge.caller == id => Some((edge, edge.callee)),
+                        Direction::Backward if edge.callee == id => Some((edge, edge.caller)),
+                        _ => None,
+                    })
+                    .map(|(edge, neighbor)| Neighbor {
+                        edge,
+                        node: self.nodes[&neighbor],
+                    })
+                    .collect();
+                json(&Neighbors { node, neighbors })
+            }
+            ["reachable"] => {
+                let roots = match (parameters.get("pattern"), parameters.get("id")) {
+                    (Some(pattern), _) => self.callgraph.find_functions(pattern),
+                    (None, Some(id)) => vec![parse(id, "id")?],
+                    (None, None) => return Err(String::from("Missing parameter: pattern or id")),
+                };
+                let depth = match parameters.get("depth") {
+                    Some(depth) => Some(parse(depth, "depth")?),
+                    None => None,
+                };
+                let subgraph = self.callgraph.reachable(
+                    &roots,
+                    direction(parameters)?,
+                    depth,
+                    edge_filter(parameters)?,
+                );
+                let mut body = Vec::new();
+                subgraph
+                    .write(OutputFormat::Json, &mut body)
+                    .map_err(|error| error.to_string())?;
+                (200, body)
+            }
+            ["impls"] => {
+                let pattern = required(parameters, "trait")?;
+                json(&self.types.find_trait_impls(pattern))
+            }
+            ["packages"] => {
+                let mut body = Vec::new();
+                self.metrics
+                    .write_json(MetricsLevel::Package, &mut body)
+                    .map_err(|error| error.to_string())?;
+                (200, body)
+            }
+            _ => error(404, &format!("Unknown endpoint: {}", path)),
+        };
+        Ok(response)
+    }
+}
+
+// Reads a line as bytes, which need not be valid UTF-8. Returns false if the line is cut off by
+// the size limit, by the end of the stream or by the timeout.
+fn read_line<R: BufRead>(reader: &mut R, line: &mut Vec<u8>) -> io::Result<bool> {
+    match reader.read_until(b'\n', line) {
+        Ok(_) => Ok(line.ends_with(b"\n")),
+        Err(error)
+            if error.kind() == io::ErrorKind::WouldBlock
+                || error.kind() == io::ErrorKind::TimedOut =>
+        {
+            Ok(false)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+fn json<T: Serialize + ?Sized>(value: &T) -> Response {
+    (200, serde_json::to_vec_pretty(value).unwrap())
+}
+
+fn error(status: u16, message: &str) -> Response {
+    let error = Error {
+        error: message.to_string(),
+    };
+    (status, serde_json::to_vec_pretty(&error).unwrap())
+}
+
+fn required<'p>(parameters: &'p HashMap<String, String>, name: &str) -> Result<&'p str, String> {
+    parameters
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| format!("Missing parameter: {}", name))
+}
+
+fn parse<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value of {}: {}", name, value))
+}
+
+fn direction(parameters: &HashMap<String, String>) -> Result<Direction, String> {
+    match parameters.get("direction").map(String::as_str) {
+        None | Some("forward") => Ok(Direction::Forward),
+        Some("backward") => Ok(Direction::Backward),
+        Some(direction) => Err(format!("Unknown direction: {}", direction)),
+    }
+}
+
+fn edge_filter(parameters: &HashMap<String, String>) -> Result<EdgeFilter, String> {
+    match parameters.get("edges") {
+        Some(edges) => edges.parse(),
+        None => Ok(EdgeFilter::All),
+    }
+}
+
+// Decodes the `%XX` escapes and the `+` spaces of a query string component.
+fn percent_decode(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        index += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,221 @@
+use crate::info::{FunctionsInfo, InterningInfo, TypeInfo};
+use crate::query::{def_path_matches, glob_match, strip_disambiguators};
+use corpus_database::tables::Tables;
+use corpus_database::types::{Build, DefPath};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{self, Write};
+
+const HELP: &str = "\
+Commands:
+    find <pattern>       The functions whose relative def path matches the pattern.
+    callers <pattern>    The functions that call the matched functions.
+    callees <pattern>    The functions that are called by the matched functions.
+    impls <pattern>      The impls of the types whose name matches the pattern, e.g., `*::Vec<*>`.
+    builds <pattern>     The builds of the crates (or packages) whose name matches the pattern,
+                         together with their crate types and cfgs.
+    help                 Print this message.
+    quit                 Exit.
+The patterns may contain the wildcards `*` and `?`.";
+
+/// Answers queries about the functions, calls, types and builds of a database that is loaded
+/// once. The calls are the ones recorded by the extractor, i.e., a virtual call is a call to the
+/// trait method.
+pub struct QueryShell<'a> {
+    tables: &'a Tables,
+    interning: InterningInfo<'a>,
+    functions: FunctionsInfo<'a>,
+    types: TypeInfo,
+    callers: HashMap<DefPath, BTreeSet<DefPath>>,
+    callees: HashMap<DefPath, BTreeSet<DefPath>>,
+    // Mapping from build to its crate types.
+    crate_types: HashMap<Build, Vec<String>>,
+    // Mapping from build to its cfgs, sorted.
+    cfgs: HashMap<Build, Vec<String>>,
+}
+
+impl<'a> QueryShell<'a> {
+    pub fn new(tables: &'a Tables) -> Self {
+        let mut callers: HashMap<_, BTreeSet<_>> = HashMap::new();
+        let mut callees: HashMap<_, BTreeSet<_>> = HashMap::new();
+        for (_, caller, callee) in tables.relations.call_graph.iter() {
+            callers.entry(*callee).or_default().insert(*caller);
+            callees.entry(*caller).or_default().insert(*callee);
+        }
+        let interning_tables = &tables.interning_tables;
+        let string = |interned| &interning_tables.strings[interned];
+        let mut crate_types: HashMap<_, Vec<_>> = HashMap::new();
+        for (build, crate_type) in tables.relations.build_crate_types.iter() {
+            crate_types
+                .entry(*build)
+                .or_default()
+                .push(string(*crate_type).clone());
+        }
+        let mut cfgs: HashMap<_, Vec<_>> = HashMap::new();
+        for (build, key, value) in tables.relations.crate_cfgs.iter() {
+            let key = string(interning_tables.crate_cfg_keys[*key]);
+            let cfg = match string(interning_tables.crate_cfg_values[*value]).as_str() {
+                "n/a" => key.clone(),
+                value => format!("{} = \"{}\"", key, value),
+            };
+            cfgs.entry(*build).or_default().push(cfg);
+        }
+        for build_cfgs in cfgs.values_mut() {
+            build_cfgs.sort();
+        }
+        Self {
+            tables,
+            interning: InterningInfo::new(&tables.interning_tables),
+            functions: FunctionsInfo::new(tables),
+            types: TypeInfo::new(tables),
+            callers,
+            callees,
+            crate_types,
+            cfgs,
+        }
+    }
+    /// Executes a single command line and writes its answer to `writer`. Returns false if the
+    /// command asks to exit. Empty lines and lines starting with `#` are ignored.
+    pub fn execute<W: Write>(&self, line: &str, writer: &mut W) -> io::Result<bool> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(true);
+        }
+        let mut tokens = line.splitn(2, char::is_whitespace);
+        let command = tokens.next().unwrap_or_default();
+        let argument = tokens.next().map(str::trim).unwrap_or_default();
+        match (command, argument) {
+            ("quit", _) | ("exit", _) => return Ok(false),
+            ("help", _) => writeln!(writer, "{}", HELP)?,
+            ("find", pattern) if !pattern.is_empty() => {
+                for def_path in self.find_functions(pattern) {
+                    self.write_function(writer, &def_path, "")?;
+                }
+            }
+            ("callers", pattern) | ("callees", pattern) if !pattern.is_empty() => {
+                let calls = match command {
+                    "callers" => &self.callers,
+                    _ => &self.callees,
+                };
+                for def_path in self.find_functions(pattern) {
+                    self.write_function(writer, &def_path, "")?;
+                    for other in calls.get(&def_path).into_iter().flatten() {
+                        self.write_function(writer, other, "    ")?;
+                    }
+                }
+            }
+            ("impls", pattern) if !pattern.is_empty() => self.write_impls(writer, pattern)?,
+            ("builds", pattern) if !pattern.is_empty() => self.write_builds(writer, pattern)?,
+            _ => writeln!(writer, "Unknown command: {} (try `help`)", line)?,
+        }
+        Ok(true)
+    }
+    // Returns the functions that match the pattern, sorted by their relative def path. These are
+    // the functions defined in the database and the callers and callees of the calls, which may
+    // be defined in crates that are not in the database.
+    fn find_functions(&self, pattern: &str) -> Vec<DefPath> {
+        let def_paths: HashSet<&DefPath> = self
+            .functions
+            .iter_def_paths()
+            .chain(self.callers.keys())
+            .chain(self.callees.keys())
+            .collect();
+        let mut functions: Vec<_> = def_paths
+            .into_iter()
+            .map(|def_path| (self.interning.def_path_to_string(def_path), *def_path))
+            .filter(|(relative_def_id, _)| def_path_matches(pattern, relative_def_id))
+            .collect();
+        functions.sort();
+        functions
+            .into_iter()
+            .map(|(_, def_path)| def_path)
+            .collect()
+    }
+    fn write_function<W: Write>(
+        &self,
+        writer: &mut W,
+        def_path: &DefPath,
+        indent: &str,
+    ) -> io::Result<()> {
+        write!(
+            writer,
+            "{}{} ({})",
+            indent,
+            self.interning.def_path_to_string(def_path),
+            self.package_label(def_path)
+        )?;
+        if let Some(details) = self.functions.function_details(def_path, &self.types) {
+            write!(
+                writer,
+                " fn({}) -> {}",
+                details.parameter_types.join(", "),
+                details.return_type
+            )?;
+        }
+        if let Some(location) = self.functions.functions_source_location(def_path) {
+            write!(writer, " at {}", location)?;
+        }
+        writeln!(writer)
+    }
+    fn write_impls<W: Write>(&self, writer: &mut W, pattern: &str) -> io::Result<()> {
+        let mut impls = Vec::new();
+        for impl_def_path in self.types.iter_impl_def_paths() {
+            let (trait_def_path, typ) = self.types.get_impl_types(impl_def_path);
+            let (type_name, _) = self.types.resolve_type(&typ, &self.interning);
+            if !glob_match(pattern, &type_name) {
+                continue;
+            }
+            let header = match trait_def_path {
+                Some(trait_def_path) => format!(
+                    "impl {} for {}",
+                    strip_disambiguators(&self.interning.def_path_to_string(&trait_def_path)),
+                    type_name
+                ),
+                None => format!("impl {}", type_name),
+            };
+            impls.push((
+                type_name,
+                header,
+                self.interning.def_path_to_string(impl_def_path),
+                self.package_label(impl_def_path),
+            ));
+        }
+        impls.sort();
+        for (_, header, relative_def_id, package) in impls {
+            writeln!(writer, "{} ({}, {})", header, relative_def_id, package)?;
+        }
+        Ok(())
+    }
+    fn write_builds<W: Write>(&self, writer: &mut W, pattern: &str) -> io::Result<()> {
+        let interning_tables = &self.tables.interning_tables;
+        let string = |interned| &interning_tables.strings[interned];
+        for (build, (package, version, crate_name, _, edition)) in interning_tables.builds.iter() {
+            let package = string(interning_tables.package_names[*package]);
+            let crate_name = string(interning_tables.crate_names[*crate_name]);
+            if !glob_match(pattern, crate_name) && !glob_match(pattern, package) {
+                continue;
+            }
+            writeln!(
+                writer,
+                "{} {} {} (edition {}, {})",
+                package,
+                string(interning_tables.package_versions[*version]),
+                crate_name,
+                string(interning_tables.editions[*edition]),
+                self.crate_types
+                    .get(&build)
+                    .map_or(String::new(), |crate_types| crate_types.join(", "))
+            )?;
+            for cfg in self.cfgs.get(&build).into_iter().flatten() {
+                writeln!(writer, "    {}", cfg)?;
+            }
+        }
+        Ok(())
+    }
+    // Returns a label of the form "package version".
+    fn package_label(&self, def_path: &DefPath) -> String {
+        match self.interning.def_path_to_package(def_path) {
+            Some((name, version)) => format!("{} {}", name, version),
+            None => String::from("unknown package"),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::info::{InterningInfo, TypeInfo};
+use crate::query::def_path_matches;
 use corpus_database::types::Type as CorpusType;
 use corpus_database::types::*;
 use serde::Serialize;
@@ -32,11 +33,35 @@ pub struct Impl {
     relative_def_id: String,
 }
 
+#[derive(Serialize)]
+pub struct Supertrait {
+    trait_id: usize,
+    supertrait_id: usize,
+    is_auto: bool,
+}
+
+/// An impl of a trait together with its self type.
+#[derive(Serialize)]
+pub struct TraitImpl<'a> {
+    #[serde(flatten)]
+    impl_def: &'a Impl,
+    self_type: &'a Type,
+}
+
+/// A trait together with its impls.
+#[derive(Serialize)]
+pub struct TraitImpls<'a> {
+    #[serde(rename = "trait")]
+    trait_def: &'a Trait,
+    impls: Vec<TraitImpl<'a>>,
+}
+
 #[derive(Default, Serialize)]
 pub struct TypeHierarchy {
     types: Vec<Type>,
     traits: Vec<Trait>,
     impls: Vec<Impl>,
+    supertraits: Vec<Supertrait>,
     #[serde(skip)]
     type_registry: HashMap<String, usize>,
     #[serde(skip)]
@@ -46,15 +71,34 @@ pub struct TypeHierarchy {
 impl TypeHierarchy {
     pub(crate) fn new(types: &TypeInfo, interning: &InterningInfo) -> Self {
         let mut type_hierarchy = TypeHierarchy::default();
-        for typ in types.iter_adt_types() {
+        // The items are registered in a sorted order, so that the ids and the output are the
+        // same in every run.
+        let mut adt_types: Vec<_> = types.iter_adt_types().collect();
+        adt_types.sort();
+        for typ in adt_types {
             type_hierarchy.register_type(*typ, types, &interning);
         }
-        for def_path in types.iter_trait_def_paths() {
+        let mut trait_def_paths: Vec<_> = types.iter_trait_def_paths().collect();
+        trait_def_paths.sort();
+        for def_path in trait_def_paths {
             type_hierarchy.register_trait(*def_path, interning);
         }
-        for def_path in types.iter_impl_def_paths() {
+        let mut impl_def_paths: Vec<_> = types.iter_impl_def_paths().collect();
+        impl_def_paths.sort();
+        for def_path in impl_def_paths {
             type_hierarchy.register_impl(*def_path, types, &interning);
         }
+        let mut supertraits: Vec<_> = types.iter_supertraits().collect();
+        supertraits.sort();
+        for (def_path, supertrait_def_path, is_auto) in supertraits {
+            let trait_id = type_hierarchy.register_trait(*def_path, interning);
+            let supertrait_id = type_hierarchy.register_trait(*supertrait_def_path, interning);
+            type_hierarchy.supertraits.push(Supertrait {
+                trait_id,
+                supertrait_id,
+                is_auto,
+            });
+        }
         type_hierarchy
     }
     fn register_type(
@@ -146,6 +190,34 @@ impl TypeHierarchy {
             id
         }
     }
+    /// Returns the traits whose relative def path matches `pattern` (see
+    /// `CallGraph::find_functions`) together with their impls.
+    pub fn find_trait_impls(&self, pattern: &str) -> Vec<TraitImpls<'_>> {
+        let types: HashMap<usize, &Type> = self.types.iter().map(|typ| (typ.id, typ)).collect();
+        let mut trait_impls: Vec<_> = self
+            .traits
+            .iter()
+            .filter(|trait_def| def_path_matches(pattern, &trait_def.relative_def_id))
+            .map(|trait_def| TraitImpls {
+                trait_def,
+                impls: Vec::new(),
+            })
+            .collect();
+        let indices: HashMap<usize, usize> = trait_impls
+            .iter()
+            .enumerate()
+            .map(|(index, trait_impls)| (trait_impls.trait_def.id, index))
+            .collect();
+        for impl_def in &self.impls {
+            if let Some(index) = impl_def.trait_id.and_then(|id| indices.get(&id)) {
+                trait_impls[*index].impls.push(TraitImpl {
+                    impl_def,
+                    self_type: types[&impl_def.type_id],
+                });
+            }
+        }
+        trait_impls
+    }
     pub fn save(&self, path: &std::path::Path) {
         let mut file =
             File::create(path).unwrap_or_else(|e| panic!("Unable to create {:?}: {}", path, e));
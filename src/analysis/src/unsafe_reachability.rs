@@ -0,0 +1,210 @@
+use crate::callgraph::{CallGraph, NodeId};
+use crate::info::UnsafeCodeInfo;
+use crate::query::EdgeFilter;
+use corpus_database::types::Unsafety;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+use std::rc::Rc;
+
+#[derive(Serialize)]
+pub struct FunctionUnsafeReachability {
+    id: NodeId,
+    relative_def_id: String,
+    // True if the function itself uses unsafe code.
+    uses_unsafe: bool,
+    can_reach_unsafe: bool,
+    // A shortest call path from the function to a function that uses unsafe code. The path
+    // starts with the function itself and is empty if no such function is reachable.
+    shortest_path: Vec<String>,
+    // The number of distinct functions that use unsafe code and are reachable from the
+    // function, including the function itself.
+    num_reachable_unsafe_functions: usize,
+}
+
+#[derive(Serialize)]
+pub struct PackageUnsafeReachability {
+    package_name: Option<String>,
+    package_version: Option<String>,
+    num_functions: usize,
+    num_unsafe_functions: usize,
+    num_functions_reaching_unsafe: usize,
+    // Externally visible functions that are not declared `unsafe`, but can reach unsafe code.
+    num_safe_api_functions_reaching_unsafe: usize,
+    functions: Vec<FunctionUnsafeReachability>,
+}
+
+/// For every function, whether it can reach a function that uses unsafe code, i.e., that
+/// performs unsafe operations or contains an unsafe block, grouped by package.
+#[derive(Serialize)]
+pub struct UnsafeReachabilityReport {
+    packages: Vec<PackageUnsafeReachability>,
+}
+
+impl UnsafeReachabilityReport {
+    pub(crate) fn new(
+        callgraph: &CallGraph,
+        unsafe_code: &UnsafeCodeInfo,
+        filter: EdgeFilter,
+    ) -> Self {
+        let unsafe_functions: HashSet<NodeId> = unsafe_code
+            .iter_unsafe_functions()
+            .filter_map(|def_path| callgraph.get_node_by_def_path(def_path))
+            .cloned()
+            .collect();
+        let next_hops = callgraph.shortest_path_next_hops(&unsafe_functions, filter);
+        let reachable_counts = reachable_unsafe_counts(callgraph, &unsafe_functions, filter);
+        let relative_def_ids: HashMap<NodeId, &str> = callgraph
+            .functions
+            .iter()
+            .map(|node| (node.id, node.relative_def_id.as_str()))
+            .collect();
+        let mut packages = BTreeMap::new();
+        for node in &callgraph.functions {
+            let package = packages
+                .entry((node.package_name.clone(), node.package_version.clone()))
+                .or_insert_with(|| PackageUnsafeReachability {
+                    package_name: node.package_name.clone(),
+                    package_version: node.package_version.clone(),
+                    num_functions: 0,
+                    num_unsafe_functions: 0,
+                    num_functions_reaching_unsafe: 0,
+                    num_safe_api_functions_reaching_unsafe: 0,
+                    functions: Vec::new(),
+                });
+            let uses_unsafe = unsafe_functions.contains(&node.id);
+            let can_reach_unsafe = uses_unsafe || next_hops.contains_key(&node.id);
+            let mut shortest_path = Vec::new();
+            if can_reach_unsafe {
+                let mut current = node.id;
+                shortest_path.push(relative_def_ids[&current].to_string());
+                while let Some(next) = next_hops.get(&current) {
+                    current = *next;
+                    shortest_path.push(relative_def_ids[&current].to_string());
+                }
+            }
+            package.num_functions += 1;
+            if uses_unsafe {
+                package.num_unsafe_functions += 1;
+            }
+            if can_reach_unsafe {
+                package.num_functions_reaching_unsafe += 1;
+                let is_declared_safe = node
+                    .details
+                    .as_ref()
+                    .map_or(false, |details| details.unsafety == Unsafety::Normal);
+                if node.is_externally_visible && is_declared_safe {
+                    package.num_safe_api_functions_reaching_unsafe += 1;
+                }
+            }
+            package.functions.push(FunctionUnsafeReachability {
+                id: node.id,
+                relative_def_id: node.relative_def_id.clone(),
+                uses_unsafe,
+                can_reach_unsafe,
+                shortest_path,
+                num_reachable_unsafe_functions: reachable_counts
+                    .get(&node.id)
+                    .cloned()
+                    .unwrap_or(0),
+            });
+        }
+        Self {
+            packages: packages.into_iter().map(|(_, package)| package).collect(),
+        }
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)
+    }
+}
+
+// Counts the distinct `targets` that are reachable from every function. The sets of reachable
+// targets are computed per strongly connected component in reverse topological order, are
+// shared between components where possible, and are freed once all the components that call a
+// component are done.
+fn reachable_unsafe_counts(
+    callgraph: &CallGraph,
+    targets: &HashSet<NodeId>,
+    filter: EdgeFilter,
+) -> HashMap<NodeId, usize> {
+    let target_bits: HashMap<NodeId, usize> = targets
+        .iter()
+        .enumerate()
+        .map(|(bit, node)| (*node, bit))
+        .collect();
+    let num_words = (targets.len() + 63) / 64;
+    // Tarjan's algorithm returns the components in reverse topological order.
+    let components = callgraph.strongly_connected_components(filter);
+    let mut node_to_component = HashMap::new();
+    for (index, component) in components.iter().enumerate() {
+        for node in component {
+            node_to_component.insert(*node, index);
+        }
+    }
+    let mut successors: HashMap<usize, HashSet<usize>> = HashMap::new();
+    for edge in &callgraph.function_calls {
+        if !filter.accepts(edge.kind) {
+            continue;
+        }
+        let caller = node_to_component[&edge.caller];
+        let callee = node_to_component[&edge.callee];
+        if caller != callee {
+            successors.entry(caller).or_default().insert(callee);
+        }
+    }
+    let mut num_predecessors = vec![0usize; components.len()];
+    for callees in successors.values() {
+        for callee in callees {
+            num_predecessors[*callee] += 1;
+        }
+    }
+    let mut reachable: Vec<Option<Rc<Vec<u64>>>> = Vec::with_capacity(components.len());
+    let mut counts = HashMap::new();
+    for (index, component) in components.iter().enumerate() {
+        let own_bits: Vec<usize> = component
+            .iter()
+            .filter_map(|node| target_bits.get(node))
+            .cloned()
+            .collect();
+        let mut inherited: Vec<&Rc<Vec<u64>>> = Vec::new();
+        for successor in successors.get(&index).into_iter().flatten() {
+            if let Some(set) = &reachable[*successor] {
+                if !inherited.iter().any(|other| Rc::ptr_eq(other, set)) {
+                    inherited.push(set);
+                }
+            }
+        }
+        let set = if own_bits.is_empty() && inherited.len() <= 1 {
+            inherited.first().map(|set| Rc::clone(set))
+        } else {
+            let mut words = vec![0u64; num_words];
+            for set in inherited {
+                for (word, other) in words.iter_mut().zip(set.iter()) {
+                    *word |= other;
+                }
+            }
+            for bit in own_bits {
+                words[bit / 64] |= 1 << (bit % 64);
+            }
+            Some(Rc::new(words))
+        };
+        if let Some(set) = &set {
+            let count: u32 = set.iter().map(|word| word.count_ones()).sum();
+            for node in component {
+                counts.insert(*node, count as usize);
+            }
+        }
+        reachable.push(set);
+        for successor in successors.get(&index).into_iter().flatten() {
+            num_predecessors[*successor] -= 1;
+            if num_predecessors[*successor] == 0 {
+                reachable[*successor] = None;
+            }
+        }
+        if num_predecessors[index] == 0 {
+            reachable[index] = None;
+        }
+    }
+    counts
+}
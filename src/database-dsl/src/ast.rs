@@ -106,6 +106,40 @@ impl InterningTable {
             path: self.key.name.clone().into(),
         })
     }
+    /// The types of the values. A tuple has one type per element.
+    pub fn get_value_types(&self) -> Vec<&syn::Type> {
+        match self.value {
+            syn::Type::Tuple(ref tuple) => tuple.elems.iter().collect(),
+            ref value => vec![value],
+        }
+    }
+    /// The names of the values when they are stored as columns: `value` for a single value and
+    /// the snake case names of the types for the elements of a tuple.
+    pub fn get_value_names(&self) -> Vec<String> {
+        let value_types = self.get_value_types();
+        if value_types.len() == 1 {
+            return vec![String::from("value")];
+        }
+        let mut names = Vec::new();
+        for (i, value_type) in value_types.iter().enumerate() {
+            let mut name = String::new();
+            for c in get_type_name(value_type).chars() {
+                if c.is_uppercase() {
+                    if !name.is_empty() {
+                        name.push('_');
+                    }
+                    name.extend(c.to_lowercase());
+                } else {
+                    name.push(c);
+                }
+            }
+            if value_types[..i].contains(value_type) {
+                name = format!("{}_{}", name, i);
+            }
+            names.push(name);
+        }
+        names
+    }
 }
 
 /// A definition of an enum.
@@ -189,6 +223,24 @@ impl DatabaseSchema {
         }
         panic!("Type {:?} is not an identifier.", typ);
     }
+    /// Finds the interning tables that map an interned id of the given type to the string it
+    /// stands for, starting with the table of the id. Returns `None` if the id does not stand
+    /// for a string, e.g., if it stands for a tuple.
+    pub fn find_string_interning_chain(&self, typ: &syn::Type) -> Option<Vec<&InterningTable>> {
+        let table = self
+            .interning_tables
+            .iter()
+            .find(|table| &table.get_key_type() == typ)?;
+        match table.value {
+            syn::Type::Tuple(_) => None,
+            ref value if get_type_name(value) == "String" => Some(vec![table]),
+            ref value => {
+                let mut chain = self.find_string_interning_chain(value)?;
+                chain.insert(0, table);
+                Some(chain)
+            }
+        }
+    }
     pub fn find_interning_table(&self, name: &syn::Ident) -> Option<&InterningTable> {
         for table in &self.interning_tables {
             if &table.name == name {
@@ -206,3 +258,13 @@ impl DatabaseSchema {
         None
     }
 }
+
+/// Returns the name of a type that is a single identifier such as `u32` or `DefPath`.
+pub fn get_type_name(typ: &syn::Type) -> String {
+    if let syn::Type::Path(syn::TypePath { qself: None, path }) = typ {
+        if let Some(ident) = path.get_ident() {
+            return ident.to_string();
+        }
+    }
+    panic!("Type {:?} is not an identifier.", typ);
+}
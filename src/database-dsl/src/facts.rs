@@ -0,0 +1,133 @@
+//! Generates the access to the facts of the relations and the interning tables by their names.
+
+use crate::ast::{self, get_type_name};
+use crate::generator::is_numeric_type;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+pub(crate) fn generate_fact_functions(schema: &ast::DatabaseSchema) -> TokenStream {
+    let mut column_arms = TokenStream::new();
+    let mut visit_arms = TokenStream::new();
+    for relation in &schema.relations {
+        let name = relation.name.to_string();
+        let field = &relation.name;
+        let mut params = TokenStream::new();
+        let mut values = Vec::new();
+        let mut columns = Vec::new();
+        for (i, parameter) in relation.parameters.iter().enumerate() {
+            let param = syn::Ident::new(&format!("p{}", i), Span::call_site());
+            values.push(generate_value(schema, &parameter.typ, quote! {#param}));
+            params.extend(quote! {#param,});
+            columns.push(parameter.name.to_string());
+        }
+        column_arms.extend(quote! {
+            #name => Some(&[#(#columns),*]),
+        });
+        visit_arms.extend(quote! {
+            #name => {
+                for (#params) in self.relations.#field.iter() {
+                    visitor(&[#(#values),*]);
+                }
+            }
+        });
+    }
+    for table in &schema.interning_tables {
+        let name = table.name.to_string();
+        let field = &table.name;
+        let mut params = TokenStream::new();
+        let mut values = vec![generate_value(schema, &table.get_key_type(), quote! {&key})];
+        let mut columns = vec![String::from("id")];
+        for (i, (value_name, value_type)) in table
+            .get_value_names()
+            .into_iter()
+            .zip(table.get_value_types())
+            .enumerate()
+        {
+            let param = syn::Ident::new(&format!("v{}", i), Span::call_site());
+            values.push(generate_value(schema, value_type, quote! {#param}));
+            params.extend(quote! {#param,});
+            columns.push(value_name);
+        }
+        let pattern = match table.value {
+            syn::Type::Tuple(_) => quote! {(#params)},
+            _ => quote! {v0},
+        };
+        column_arms.extend(quote! {
+            #name => Some(&[#(#columns),*]),
+        });
+        visit_arms.extend(quote! {
+            #name => {
+                for (key, #pattern) in self.interning_tables.#field.iter() {
+                    visitor(&[#(#values),*]);
+                }
+            }
+        });
+    }
+    quote! {
+        /// A column of a fact. The ids, the numbers and the booleans are numbers, while the
+        /// strings, the enums and the ids that are not numbers are text.
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum FactValue<'a> {
+            Number(u64),
+            Text(std::borrow::Cow<'a, str>),
+        }
+
+        impl Tables {
+            /// Returns the column names of the relation or the interning table `name`, or
+            /// `None` if there is no such relation.
+            pub fn fact_columns(name: &str) -> Option<&'static [&'static str]> {
+                match name {
+                    #column_arms
+                    _ => None,
+                }
+            }
+            /// Calls `visitor` with the columns of every fact of the relation or the interning
+            /// table `name`. Returns false if there is no such relation.
+            pub fn visit_facts<'a>(
+                &'a self,
+                name: &str,
+                mut visitor: impl FnMut(&[FactValue<'a>])
+            ) -> bool {
+                match name {
+                    #visit_arms
+                    _ => return false,
+                }
+                true
+            }
+        }
+    }
+}
+
+/// Generates an expression that converts the value referenced by `value` into a `FactValue`.
+fn generate_value(
+    schema: &ast::DatabaseSchema,
+    typ: &syn::Type,
+    value: TokenStream,
+) -> TokenStream {
+    if get_type_name(typ) == "String" {
+        return quote! { FactValue::Text(std::borrow::Cow::Borrowed(#value.as_str())) };
+    }
+    let number = quote! { FactValue::Number(u64::from((#value).0)) };
+    let text = quote! { FactValue::Text(std::borrow::Cow::Owned(#value.to_string())) };
+    match schema.get_type_kind(typ) {
+        ast::TypeKind::IncrementalId(_) | ast::TypeKind::InternedId(_) => number,
+        ast::TypeKind::CustomId => {
+            let is_numeric = schema
+                .custom_ids
+                .iter()
+                .any(|id| get_type_name(typ) == id.name.to_string() && is_numeric_type(&id.typ));
+            if is_numeric {
+                number
+            } else {
+                quote! {
+                    FactValue::Text(std::borrow::Cow::Owned(format!("{:?}", (#value).0)))
+                }
+            }
+        }
+        ast::TypeKind::Enum => text,
+        ast::TypeKind::RustType => match get_type_name(typ).as_str() {
+            "u128" => text,
+            _ => quote! { FactValue::Number(u64::from(*#value)) },
+        },
+    }
+}
@@ -1,4 +1,7 @@
 use crate::ast;
+use crate::facts;
+use crate::souffle;
+use crate::sql;
 use log::debug;
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens};
@@ -12,6 +15,9 @@ pub(crate) fn generate_tokens(schema: ast::DatabaseSchema) -> TokenStream {
     let load_save_functions = generate_load_save_functions(&schema);
     let loader_functions = generate_loader_functions(&schema);
     let merge_functions = generate_merge_functions(&schema);
+    let souffle_functions = souffle::generate_souffle_functions(&schema);
+    let sql_functions = sql::generate_sql_functions(&schema);
+    let fact_functions = facts::generate_fact_functions(&schema);
     quote! {
         pub mod types {
             use serde_derive::{Deserialize, Serialize};
@@ -51,6 +57,12 @@ pub(crate) fn generate_tokens(schema: ast::DatabaseSchema) -> TokenStream {
 
             #load_save_functions
 
+            #souffle_functions
+
+            #sql_functions
+
+            #fact_functions
+
             pub struct Loader {
                 pub(crate) database_root: PathBuf,
             }
@@ -103,7 +115,7 @@ fn generate_id_types(schema: &ast::DatabaseSchema) -> TokenStream {
     tokens
 }
 
-fn is_numeric_type(typ: &syn::Type) -> bool {
+pub(crate) fn is_numeric_type(typ: &syn::Type) -> bool {
     if let syn::Type::Path(syn::TypePath {
         qself: None,
         ref path,
@@ -3,8 +3,11 @@ use std::io::{Read, Write};
 use std::path::Path;
 
 mod ast;
+mod facts;
 mod generator;
 mod parser;
+mod souffle;
+mod sql;
 
 pub fn parse_schema(path: &Path) -> ast::DatabaseSchema {
     let mut file = File::open(path).unwrap();
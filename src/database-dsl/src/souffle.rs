@@ -0,0 +1,322 @@
+//! Generates the export of the database as Soufflé facts together with the matching Soufflé
+//! declarations.
+
+use crate::ast::{self, get_type_name};
+use crate::generator::is_numeric_type;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+/// Soufflé keywords that cannot be used as attribute names.
+const KEYWORDS: &[&str] = &[
+    "as",
+    "band",
+    "bnot",
+    "bor",
+    "bshl",
+    "bshr",
+    "bshru",
+    "bxor",
+    "cat",
+    "contains",
+    "count",
+    "false",
+    "land",
+    "lnot",
+    "lor",
+    "match",
+    "max",
+    "mean",
+    "min",
+    "nil",
+    "number",
+    "ord",
+    "range",
+    "strlen",
+    "substr",
+    "sum",
+    "symbol",
+    "to_number",
+    "to_string",
+    "true",
+    "unsigned",
+];
+
+pub(crate) fn generate_souffle_functions(schema: &ast::DatabaseSchema) -> TokenStream {
+    let declarations = generate_declarations(schema, false);
+    let resolved_declarations = generate_declarations(schema, true);
+    let mut relation_tokens = TokenStream::new();
+    for relation in &schema.relations {
+        let name = &relation.name;
+        let file_name = format!("{}.facts", name);
+        let mut params = TokenStream::new();
+        let mut columns = Vec::new();
+        let mut resolved_columns = Vec::new();
+        for (i, parameter) in relation.parameters.iter().enumerate() {
+            let param = syn::Ident::new(&format!("p{}", i), Span::call_site());
+            params.extend(quote! {#param,});
+            columns.push(generate_column(
+                schema,
+                &parameter.typ,
+                quote! {#param},
+                false,
+            ));
+            resolved_columns.push(generate_column(
+                schema,
+                &parameter.typ,
+                quote! {#param},
+                true,
+            ));
+        }
+        let format = facts_format(columns.len());
+        relation_tokens.extend(quote! {
+            let mut writer = std::io::BufWriter::new(
+                std::fs::File::create(directory.join(#file_name))?
+            );
+            for (#params) in self.relations.#name.iter() {
+                if resolve_interned {
+                    write!(writer, #format, #(#resolved_columns),*)?;
+                } else {
+                    write!(writer, #format, #(#columns),*)?;
+                }
+            }
+            writer.flush()?;
+        });
+    }
+    let mut interning_tokens = TokenStream::new();
+    for table in &schema.interning_tables {
+        let name = &table.name;
+        let file_name = format!("{}.facts", name);
+        let key_column = generate_column(schema, &table.get_key_type(), quote! {&key}, false);
+        let mut params = TokenStream::new();
+        let mut columns = vec![key_column.clone()];
+        let mut resolved_columns = vec![key_column];
+        for (i, value_type) in table.get_value_types().into_iter().enumerate() {
+            let param = syn::Ident::new(&format!("v{}", i), Span::call_site());
+            params.extend(quote! {#param,});
+            columns.push(generate_column(schema, value_type, quote! {#param}, false));
+            resolved_columns.push(generate_column(schema, value_type, quote! {#param}, true));
+        }
+        let format = facts_format(columns.len());
+        let pattern = match table.value {
+            syn::Type::Tuple(_) => quote! {(#params)},
+            _ => quote! {v0},
+        };
+        let store_table = quote! {
+            let mut writer = std::io::BufWriter::new(
+                std::fs::File::create(directory.join(#file_name))?
+            );
+            for (key, #pattern) in self.interning_tables.#name.iter() {
+                if resolve_interned {
+                    write!(writer, #format, #(#resolved_columns),*)?;
+                } else {
+                    write!(writer, #format, #(#columns),*)?;
+                }
+            }
+            writer.flush()?;
+        };
+        // The tables whose keys are resolved to strings are inlined into the other facts.
+        if schema
+            .find_string_interning_chain(&table.get_key_type())
+            .is_some()
+        {
+            interning_tokens.extend(quote! {
+                if !resolve_interned {
+                    #store_table
+                }
+            });
+        } else {
+            interning_tokens.extend(store_table);
+        }
+    }
+    quote! {
+        impl Tables {
+            /// Writes every relation and interning table as a tab-separated Soufflé `.facts`
+            /// file into `directory` together with their declarations in `schema.dl`. If
+            /// `resolve_interned` is true, the interned ids that stand for strings are written
+            /// as the strings themselves.
+            pub fn store_souffle(
+                &self,
+                directory: &Path,
+                resolve_interned: bool
+            ) -> Result<(), Error> {
+                use std::io::Write;
+                std::fs::create_dir_all(directory)?;
+                let declarations = if resolve_interned {
+                    #resolved_declarations
+                } else {
+                    #declarations
+                };
+                std::fs::write(directory.join("schema.dl"), declarations)?;
+                #relation_tokens
+                #interning_tokens
+                Ok(())
+            }
+        }
+
+        /// Escapes the characters that would break the tab-separated format.
+        fn souffle_symbol(value: &str) -> std::borrow::Cow<'_, str> {
+            if value.contains(|c| c == '\t' || c == '\n' || c == '\r' || c == '\\') {
+                std::borrow::Cow::Owned(
+                    value
+                        .replace('\\', "\\\\")
+                        .replace('\t', "\\t")
+                        .replace('\n', "\\n")
+                        .replace('\r', "\\r")
+                )
+            } else {
+                std::borrow::Cow::Borrowed(value)
+            }
+        }
+    }
+}
+
+/// Generates the Soufflé declarations of the id types, the relations and the interning tables.
+fn generate_declarations(schema: &ast::DatabaseSchema, resolve_interned: bool) -> String {
+    let mut declarations = String::from("// Generated from schema.dl.\n\n");
+    for id in &schema.incremental_ids {
+        let typ = souffle_number_type(&id.typ);
+        declarations.push_str(&format!(".type {} <: {}\n", id.name, typ));
+    }
+    for id in &schema.custom_ids {
+        let typ = if is_numeric_type(&id.typ) {
+            souffle_number_type(&id.typ)
+        } else {
+            "symbol"
+        };
+        declarations.push_str(&format!(".type {} <: {}\n", id.name, typ));
+    }
+    for table in &schema.interning_tables {
+        let typ = if resolve_interned
+            && schema
+                .find_string_interning_chain(&table.get_key_type())
+                .is_some()
+        {
+            "symbol"
+        } else {
+            souffle_number_type(&table.key.typ)
+        };
+        declarations.push_str(&format!(".type {} <: {}\n", table.key.name, typ));
+    }
+    for enum_info in &schema.enums {
+        declarations.push_str(&format!(".type {} <: symbol\n", enum_info.item.ident));
+    }
+    for relation in &schema.relations {
+        let attributes: Vec<_> = relation
+            .parameters
+            .iter()
+            .map(|parameter| (parameter.name.to_string(), &parameter.typ))
+            .collect();
+        declarations.push_str(&generate_declaration(&relation.name, &attributes));
+    }
+    for table in &schema.interning_tables {
+        if resolve_interned
+            && schema
+                .find_string_interning_chain(&table.get_key_type())
+                .is_some()
+        {
+            continue;
+        }
+        let key_type = table.get_key_type();
+        let mut attributes = vec![(String::from("id"), &key_type)];
+        attributes.extend(
+            table
+                .get_value_names()
+                .into_iter()
+                .zip(table.get_value_types()),
+        );
+        declarations.push_str(&generate_declaration(&table.name, &attributes));
+    }
+    declarations
+}
+
+fn generate_declaration(name: &syn::Ident, attributes: &[(String, &syn::Type)]) -> String {
+    let attributes: Vec<_> = attributes
+        .iter()
+        .map(|(attribute, typ)| {
+            let attribute = if KEYWORDS.contains(&attribute.as_str()) {
+                format!("{}_", attribute)
+            } else {
+                attribute.clone()
+            };
+            format!("{}: {}", attribute, souffle_type(typ))
+        })
+        .collect();
+    format!(
+        "\n.decl {}({})\n.input {}\n",
+        name,
+        attributes.join(", "),
+        name
+    )
+}
+
+/// The Soufflé type of a column of the given Rust type.
+fn souffle_type(typ: &syn::Type) -> String {
+    match get_type_name(typ).as_str() {
+        "bool" => String::from("number"),
+        "u8" | "u16" | "u32" | "u64" | "usize" => String::from(souffle_number_type(typ)),
+        "u128" | "String" => String::from("symbol"),
+        name => String::from(name),
+    }
+}
+
+/// The Soufflé type of a number of the given Rust type. Stock Soufflé has 32-bit domains, so
+/// 64-bit numbers are declared as symbols, which can still be joined on, but not compared
+/// numerically.
+fn souffle_number_type(typ: &syn::Type) -> &'static str {
+    match get_type_name(typ).as_str() {
+        "u64" | "usize" => "symbol",
+        _ => "unsigned",
+    }
+}
+
+/// Generates an expression that formats the value referenced by `value` as a Soufflé column.
+fn generate_column(
+    schema: &ast::DatabaseSchema,
+    typ: &syn::Type,
+    value: TokenStream,
+    resolve_interned: bool,
+) -> TokenStream {
+    if get_type_name(typ) == "String" {
+        return quote! { souffle_symbol(#value) };
+    }
+    match schema.get_type_kind(typ) {
+        ast::TypeKind::IncrementalId(_) => quote! { (#value).0 },
+        ast::TypeKind::CustomId => {
+            let id = schema
+                .custom_ids
+                .iter()
+                .find(|id| get_type_name(typ) == id.name.to_string())
+                .unwrap();
+            if is_numeric_type(&id.typ) {
+                quote! { (#value).0 }
+            } else {
+                quote! { souffle_symbol(&format!("{:?}", (#value).0)) }
+            }
+        }
+        ast::TypeKind::InternedId(_) => match schema.find_string_interning_chain(typ) {
+            Some(chain) if resolve_interned => {
+                let mut lookup = quote! {*#value};
+                for table in chain {
+                    let name = &table.name;
+                    lookup = quote! { self.interning_tables.#name[#lookup] };
+                }
+                quote! { souffle_symbol(&#lookup) }
+            }
+            _ => quote! { (#value).0 },
+        },
+        ast::TypeKind::Enum => quote! { #value },
+        ast::TypeKind::RustType => {
+            if get_type_name(typ) == "bool" {
+                quote! { *#value as u8 }
+            } else {
+                quote! { #value }
+            }
+        }
+    }
+}
+
+fn facts_format(columns: usize) -> String {
+    let mut format = vec!["{}"; columns].join("\t");
+    format.push('\n');
+    format
+}
@@ -0,0 +1,255 @@
+//! Generates the export of the database into an SQLite file together with its DDL.
+
+use crate::ast::{self, get_type_name};
+use crate::generator::is_numeric_type;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+/// A column of an SQL table.
+struct Column {
+    name: String,
+    sql_type: &'static str,
+    /// Whether the column stores an id that refers to another table.
+    is_id: bool,
+}
+
+pub(crate) fn generate_sql_functions(schema: &ast::DatabaseSchema) -> TokenStream {
+    let mut create_tables = String::new();
+    let mut create_indexes = String::new();
+    let mut inserts = TokenStream::new();
+    for relation in &schema.relations {
+        let name = relation.name.to_string();
+        let mut columns = Vec::new();
+        let mut params = TokenStream::new();
+        let mut values = Vec::new();
+        for (i, parameter) in relation.parameters.iter().enumerate() {
+            let param = syn::Ident::new(&format!("p{}", i), Span::call_site());
+            columns.push(generate_column(
+                schema,
+                parameter.name.to_string(),
+                &parameter.typ,
+            ));
+            values.push(generate_value(schema, &parameter.typ, quote! {#param}));
+            params.extend(quote! {#param,});
+        }
+        create_tables.push_str(&generate_create_table(&name, &columns, None));
+        create_indexes.push_str(&generate_create_indexes(&name, &columns));
+        let insert = generate_insert(&name, columns.len());
+        let field = &relation.name;
+        inserts.extend(quote! {
+            {
+                let mut statement = transaction.prepare(#insert)?;
+                for (#params) in self.relations.#field.iter() {
+                    statement.execute(rusqlite::params![#(#values),*])?;
+                }
+            }
+        });
+    }
+    let mut create_views = String::new();
+    for table in &schema.interning_tables {
+        let name = table.name.to_string();
+        let key_column = Column {
+            name: String::from("id"),
+            sql_type: "INTEGER",
+            is_id: true,
+        };
+        let mut columns = Vec::new();
+        let mut params = TokenStream::new();
+        let mut values = vec![generate_value(schema, &table.get_key_type(), quote! {&key})];
+        let value_names = table.get_value_names();
+        for (i, (value_name, value_type)) in
+            value_names.iter().zip(table.get_value_types()).enumerate()
+        {
+            let param = syn::Ident::new(&format!("v{}", i), Span::call_site());
+            columns.push(generate_column(schema, value_name.clone(), value_type));
+            values.push(generate_value(schema, value_type, quote! {#param}));
+            params.extend(quote! {#param,});
+        }
+        create_tables.push_str(&generate_create_table(&name, &columns, Some(&key_column)));
+        create_indexes.push_str(&generate_create_indexes(&name, &columns));
+        if let syn::Type::Tuple(_) = table.value {
+            create_views.push_str(&generate_create_view(schema, table));
+        }
+        let insert = generate_insert(&name, columns.len() + 1);
+        let field = &table.name;
+        let pattern = match table.value {
+            syn::Type::Tuple(_) => quote! {(#params)},
+            _ => quote! {v0},
+        };
+        inserts.extend(quote! {
+            {
+                let mut statement = transaction.prepare(#insert)?;
+                for (key, #pattern) in self.interning_tables.#field.iter() {
+                    statement.execute(rusqlite::params![#(#values),*])?;
+                }
+            }
+        });
+    }
+    let create_indexes_and_views = format!("{}{}", create_indexes, create_views);
+    let schema_sql = format!("{}{}", create_tables, create_indexes_and_views);
+    quote! {
+        /// The SQL statements that create the tables, the indexes and the views of the SQLite
+        /// export of the database.
+        pub const SQL_SCHEMA: &str = #schema_sql;
+
+        #[cfg(feature = "sqlite")]
+        impl Tables {
+            /// Writes every relation and interning table into a new SQLite database at `path`.
+            /// The ids are stored as integers and the enums as the names of their variants.
+            pub fn store_sqlite(&self, path: &Path) -> Result<(), Error> {
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+                let mut connection = rusqlite::Connection::open(path)?;
+                connection.execute_batch(#create_tables)?;
+                let transaction = connection.transaction()?;
+                #inserts
+                transaction.commit()?;
+                // The indexes are created after the rows are inserted because that is faster.
+                connection.execute_batch(#create_indexes_and_views)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+fn generate_column(schema: &ast::DatabaseSchema, name: String, typ: &syn::Type) -> Column {
+    let (sql_type, is_id) = match get_type_name(typ).as_str() {
+        "String" => ("TEXT", false),
+        _ => match schema.get_type_kind(typ) {
+            ast::TypeKind::IncrementalId(_) | ast::TypeKind::InternedId(_) => ("INTEGER", true),
+            ast::TypeKind::CustomId if is_integer_custom_id(schema, typ) => ("INTEGER", false),
+            ast::TypeKind::CustomId | ast::TypeKind::Enum => ("TEXT", false),
+            ast::TypeKind::RustType if fits_into_integer(typ) => ("INTEGER", false),
+            ast::TypeKind::RustType => ("TEXT", false),
+        },
+    };
+    Column {
+        name,
+        sql_type,
+        is_id,
+    }
+}
+
+/// Generates an expression that converts the value referenced by `value` into an SQL value.
+/// SQLite integers are signed 64-bit numbers, so the unsigned 64-bit numbers, e.g., the crate
+/// hashes, are stored as decimal strings. The incremental and interned ids are counters that
+/// start at 0, so they are stored as integers.
+fn generate_value(
+    schema: &ast::DatabaseSchema,
+    typ: &syn::Type,
+    value: TokenStream,
+) -> TokenStream {
+    if get_type_name(typ) == "String" {
+        return quote! { #value.as_str() };
+    }
+    match schema.get_type_kind(typ) {
+        ast::TypeKind::IncrementalId(_) | ast::TypeKind::InternedId(_) => {
+            quote! { (#value).0 as i64 }
+        }
+        ast::TypeKind::CustomId if is_integer_custom_id(schema, typ) => {
+            quote! { (#value).0 as i64 }
+        }
+        ast::TypeKind::CustomId => quote! { format!("{:?}", (#value).0) },
+        ast::TypeKind::Enum => quote! { #value.to_string() },
+        ast::TypeKind::RustType => match get_type_name(typ).as_str() {
+            "bool" => quote! { *#value },
+            _ if fits_into_integer(typ) => quote! { *#value as i64 },
+            _ => quote! { #value.to_string() },
+        },
+    }
+}
+
+/// Whether `typ` is a custom id that wraps a number that fits into an SQLite integer.
+fn is_integer_custom_id(schema: &ast::DatabaseSchema, typ: &syn::Type) -> bool {
+    schema.custom_ids.iter().any(|id| {
+        get_type_name(typ) == id.name.to_string()
+            && is_numeric_type(&id.typ)
+            && fits_into_integer(&id.typ)
+    })
+}
+
+/// Whether the values of the Rust type `typ` fit into a signed 64-bit SQLite integer.
+fn fits_into_integer(typ: &syn::Type) -> bool {
+    match get_type_name(typ).as_str() {
+        "u64" | "usize" | "u128" => false,
+        _ => true,
+    }
+}
+
+fn generate_create_table(name: &str, columns: &[Column], key: Option<&Column>) -> String {
+    let mut definitions = Vec::new();
+    if let Some(key) = key {
+        definitions.push(format!("    \"{}\" {} PRIMARY KEY", key.name, key.sql_type));
+    }
+    for column in columns {
+        definitions.push(format!(
+            "    \"{}\" {} NOT NULL",
+            column.name, column.sql_type
+        ));
+    }
+    format!(
+        "CREATE TABLE \"{}\" (\n{}\n);\n",
+        name,
+        definitions.join(",\n")
+    )
+}
+
+fn generate_create_indexes(name: &str, columns: &[Column]) -> String {
+    let mut indexes = String::new();
+    for column in columns.iter().filter(|column| column.is_id) {
+        indexes.push_str(&format!(
+            "CREATE INDEX \"{}_{}\" ON \"{}\" (\"{}\");\n",
+            name, column.name, name, column.name
+        ));
+    }
+    indexes
+}
+
+/// Generates a view of an interning table with a tuple value, in which the ids that stand for
+/// strings are replaced with the strings, e.g., the crate name of a def path.
+fn generate_create_view(schema: &ast::DatabaseSchema, table: &ast::InterningTable) -> String {
+    let name = &table.name;
+    let mut selected = vec![format!("    \"{}\".\"id\" AS \"id\"", name)];
+    let mut joins = Vec::new();
+    for (value_name, value_type) in table.get_value_names().iter().zip(table.get_value_types()) {
+        let chain = match get_type_name(value_type).as_str() {
+            "String" => None,
+            _ => schema.find_string_interning_chain(value_type),
+        };
+        match chain {
+            Some(chain) => {
+                let mut previous = format!("\"{}\".\"{}\"", name, value_name);
+                for (i, interning_table) in chain.iter().enumerate() {
+                    let alias = format!("{}_{}", value_name, i);
+                    joins.push(format!(
+                        "    JOIN \"{}\" AS \"{}\" ON \"{}\".\"id\" = {}",
+                        interning_table.name, alias, alias, previous
+                    ));
+                    previous = format!("\"{}\".\"value\"", alias);
+                }
+                selected.push(format!("    {} AS \"{}\"", previous, value_name));
+            }
+            None => selected.push(format!(
+                "    \"{}\".\"{}\" AS \"{}\"",
+                name, value_name, value_name
+            )),
+        }
+    }
+    format!(
+        "CREATE VIEW \"{}_resolved\" AS SELECT\n{}\nFROM \"{}\"\n{};\n",
+        name,
+        selected.join(",\n"),
+        name,
+        joins.join("\n")
+    )
+}
+
+fn generate_insert(name: &str, columns: usize) -> String {
+    let placeholders: Vec<_> = (1..=columns).map(|i| format!("?{}", i)).collect();
+    format!(
+        "INSERT INTO \"{}\" VALUES ({})",
+        name,
+        placeholders.join(", ")
+    )
+}
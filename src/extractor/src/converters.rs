@@ -112,6 +112,20 @@ impl<'tcx> ConvertInto<types::AggregateKind> for mir::AggregateKind<'tcx> {
     }
 }
 
+impl<'tcx> ConvertInto<types::AssertKind> for mir::AssertMessage<'tcx> {
+    fn convert_into(&self) -> types::AssertKind {
+        use rustc::mir::interpret::PanicInfo;
+        match self {
+            PanicInfo::BoundsCheck { .. } => types::AssertKind::BoundsCheck,
+            PanicInfo::Overflow(..) | PanicInfo::OverflowNeg => types::AssertKind::Overflow,
+            PanicInfo::DivisionByZero | PanicInfo::RemainderByZero => {
+                types::AssertKind::DivisionByZero
+            }
+            _ => types::AssertKind::Unknown,
+        }
+    }
+}
+
 impl ConvertInto<types::ScopeSafety> for Option<mir::Safety> {
     fn convert_into(&self) -> types::ScopeSafety {
         match self {
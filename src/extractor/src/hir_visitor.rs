@@ -276,6 +276,18 @@ impl<'a, 'tcx> Visitor<'tcx> for HirVisitor<'a, 'tcx> {
                         trait_item.defaultness.convert_into(),
                     )
                 }
+                let super_predicates = self.tcx.super_predicates_of(def_id);
+                for (predicate, _) in super_predicates.predicates {
+                    if let Some(trait_ref) = predicate.to_opt_poly_trait_ref() {
+                        let supertrait_def_id = trait_ref.def_id();
+                        let supertrait_def_path = self.filler.resolve_def_id(supertrait_def_id);
+                        self.filler.tables.register_trait_supertraits(
+                            item_id,
+                            supertrait_def_path,
+                            self.tcx.trait_is_auto(supertrait_def_id),
+                        );
+                    }
+                }
                 let old_item = mem::replace(&mut self.current_item, Some(item_id));
                 intravisit::walk_item(self, item);
                 self.current_item = old_item;
@@ -333,6 +345,23 @@ impl<'a, 'tcx> Visitor<'tcx> for HirVisitor<'a, 'tcx> {
                 return_type,
             ),
         };
+        if let intravisit::FnKind::Method(..) = fn_kind {
+            if let Some(parent) = self.current_item {
+                self.filler
+                    .tables
+                    .register_method_definitions(function, parent);
+            }
+        }
+        // Closures have synthetic type parameters, so the generics of the enclosing function
+        // are checked instead.
+        let base_def_id = self.tcx.closure_base_def_id(self.hir_map.local_def_id(id));
+        if self
+            .tcx
+            .generics_of(base_def_id)
+            .requires_monomorphization(self.tcx)
+        {
+            self.filler.tables.register_generic_functions(function);
+        }
         let old_item = mem::replace(&mut self.current_item, Some(function));
         intravisit::walk_fn(self, fn_kind, fn_def, body_id, span, id);
         self.current_item = old_item;
@@ -3,6 +3,7 @@
 // modified, or distributed except according to those terms.
 
 use crate::converters::ConvertInto;
+use crate::mirai_utils;
 use crate::table_filler::TableFiller;
 use crate::SubstsMap;
 use crate::SubstsSet;
@@ -165,6 +166,13 @@ impl<'a, 'b, 'tcx> MirVisitor<'a, 'b, 'tcx> {
                     mir::Rvalue::Cast(kind, operand, typ) => {
                         let interned_operand = self.visit_operand(operand);
                         let interned_type = self.filler.register_type(typ);
+                        match kind {
+                            mir::CastKind::Pointer(ty::adjustment::PointerCast::ReifyFnPointer)
+                            | mir::CastKind::Pointer(
+                                ty::adjustment::PointerCast::ClosureFnPointer(_),
+                            ) => self.visit_function_pointer_target(operand, typ),
+                            _ => {}
+                        }
                         let (stmt,) = self.filler.tables.register_statements_assign_cast(
                             interned_target_type,
                             kind.convert_into(),
@@ -229,6 +237,16 @@ impl<'a, 'b, 'tcx> MirVisitor<'a, 'b, 'tcx> {
                             interned_target_type,
                             aggregate.convert_into(),
                         );
+                        if let mir::AggregateKind::Closure(def_id, substs) = **aggregate {
+                            let sig = substs.as_closure().sig(def_id, self.tcx);
+                            let sig = self.tcx.erase_late_bound_regions(&sig);
+                            let signature =
+                                self.signature_key(sig.inputs().iter().cloned(), sig.output());
+                            let closure_def_path = self.filler.resolve_def_id(def_id);
+                            self.filler
+                                .tables
+                                .register_closure_signatures(closure_def_path, signature);
+                        }
                         for (i, operand) in operands.iter().enumerate() {
                             let interned_operand = self.visit_operand(operand);
                             self.filler
@@ -409,6 +427,12 @@ impl<'a, 'b, 'tcx> MirVisitor<'a, 'b, 'tcx> {
                     destination_block,
                     get_maybe_block(cleanup),
                 );
+                let span = self
+                    .filler
+                    .register_span(terminator.source_info.span, self.root_scope);
+                self.filler
+                    .tables
+                    .register_terminators_call_span(function_call, span);
                 for (i, arg) in args.iter().enumerate() {
                     let interned_arg = self.visit_operand(arg);
                     self.filler.tables.register_terminators_call_arg(
@@ -459,6 +483,22 @@ impl<'a, 'b, 'tcx> MirVisitor<'a, 'b, 'tcx> {
                                     );
                                     if let ty::InstanceDef::Virtual(..) = instance.def {
                                         self.filler.tables.register_virtual_calls(function_call);
+                                        if let ty::TyKind::Dynamic(binder, _) =
+                                            substs.type_at(0).kind
+                                        {
+                                            if let Some(principal) = binder.principal_def_id() {
+                                                let trait_def_path =
+                                                    self.filler.resolve_def_id(principal);
+                                                self.filler.tables.register_virtual_call_receivers(
+                                                    function_call,
+                                                    trait_def_path,
+                                                );
+                                            }
+                                        }
+                                        if self.is_fn_trait_method(id) {
+                                            // A call to a closure through a `dyn Fn*` object.
+                                            self.visit_fn_trait_object_call(function_call, substs);
+                                        }
                                     }
                                 } else {
                                     // If there are no resolved instances.
@@ -479,14 +519,36 @@ impl<'a, 'b, 'tcx> MirVisitor<'a, 'b, 'tcx> {
                             unreachable!("Unexpected called constant type: {:?}", constant);
                         }
                     }
-                    mir::Operand::Copy(_) | mir::Operand::Move(_) => {}
+                    mir::Operand::Copy(_) | mir::Operand::Move(_) => {
+                        if let ty::TyKind::FnDef(id, _) = func_ty.kind {
+                            // A function item stored in a local variable has a known target.
+                            let callee_def_path = self.filler.resolve_def_id(id);
+                            self.filler.tables.register_call_graph(
+                                function_call,
+                                self.body_path,
+                                callee_def_path,
+                            );
+                        } else {
+                            // A call through a function pointer.
+                            let sig = self.tcx.erase_late_bound_regions(&sig);
+                            let signature =
+                                self.signature_key(sig.inputs().iter().cloned(), sig.output());
+                            let interned_func_ty = self.filler.register_type(func_ty);
+                            self.filler.tables.register_indirect_calls(
+                                function_call,
+                                self.body_path,
+                                interned_func_ty,
+                                signature,
+                            );
+                        }
+                    }
                 };
                 "Call"
             }
             mir::TerminatorKind::Assert {
                 cond,
                 expected,
-                msg: _,
+                msg,
                 target,
                 cleanup,
             } => {
@@ -498,6 +560,9 @@ impl<'a, 'b, 'tcx> MirVisitor<'a, 'b, 'tcx> {
                     basic_blocks[target],
                     get_maybe_block(cleanup),
                 );
+                self.filler
+                    .tables
+                    .register_terminators_assert_kind(block, msg.convert_into());
                 "Assert"
             }
             mir::TerminatorKind::Yield {
@@ -572,6 +637,64 @@ impl<'a, 'b, 'tcx> MirVisitor<'a, 'b, 'tcx> {
         }
         instances
     }
+    /// Registers the function or closure that is coerced to the function pointer type `typ`.
+    fn visit_function_pointer_target(&mut self, operand: &mir::Operand<'tcx>, typ: ty::Ty<'tcx>) {
+        let def_id = match operand.ty(self.body, self.tcx).kind {
+            ty::TyKind::FnDef(def_id, _) | ty::TyKind::Closure(def_id, _) => def_id,
+            _ => return,
+        };
+        let sig = self.tcx.erase_late_bound_regions(&typ.fn_sig(self.tcx));
+        let signature = self.signature_key(sig.inputs().iter().cloned(), sig.output());
+        let def_path = self.filler.resolve_def_id(def_id);
+        self.filler
+            .tables
+            .register_function_pointer_targets(def_path, signature);
+    }
+    /// Registers a call to a method of a `Fn*` trait on a trait object as an indirect call.
+    /// The substitutions are the trait object type and the tuple of the argument types.
+    fn visit_fn_trait_object_call(
+        &mut self,
+        function_call: types::FunctionCall,
+        substs: SubstsRef<'tcx>,
+    ) {
+        let self_ty = substs.type_at(0);
+        let args_ty = substs.type_at(1);
+        let output = match self_ty.kind {
+            ty::TyKind::Dynamic(binder, _) => binder
+                .skip_binder()
+                .projection_bounds()
+                .next()
+                .map(|projection| projection.skip_binder().ty),
+            _ => None,
+        };
+        let output = output.unwrap_or_else(|| self.tcx.mk_unit());
+        let signature = self.signature_key(args_ty.tuple_fields(), output);
+        let interned_self_ty = self.filler.register_type(self_ty);
+        self.filler.tables.register_indirect_calls(
+            function_call,
+            self.body_path,
+            interned_self_ty,
+            signature,
+        );
+    }
+    /// Returns true if the function is a method of `Fn`, `FnMut` or `FnOnce`.
+    fn is_fn_trait_method(&self, def_id: DefId) -> bool {
+        self.tcx
+            .trait_of_item(def_id)
+            .map_or(false, |trait_def_id| {
+                self.tcx
+                    .fn_trait_kind_from_lang_item(trait_def_id)
+                    .is_some()
+            })
+    }
+    /// Returns the key of a signature, which is built from the def paths of the types in it.
+    fn signature_key(
+        &self,
+        inputs: impl Iterator<Item = ty::Ty<'tcx>>,
+        output: ty::Ty<'tcx>,
+    ) -> String {
+        mirai_utils::signature_key_str(self.tcx, inputs, output)
+    }
     fn is_substs_generic(&self, substs: &SubstsRef) -> bool {
         for typ in substs.types() {
             match typ.kind {
@@ -92,6 +92,24 @@ pub fn argument_types_key_str<'tcx>(
     Rc::new(result)
 }
 
+/// Returns a string that identifies a function signature by the def paths of the types in it,
+/// so that the signatures of functions and call sites from different crates can be compared.
+#[logfn(TRACE)]
+pub fn signature_key_str<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    inputs: impl Iterator<Item = Ty<'tcx>>,
+    output: Ty<'tcx>,
+) -> String {
+    let mut result = "fn".to_string();
+    for input in inputs {
+        result.push('_');
+        append_mangled_type(&mut result, input, tcx);
+    }
+    result.push_str("_output_");
+    append_mangled_type(&mut result, output, tcx);
+    result
+}
+
 /// Appends a string to str with the constraint that it must uniquely identify ty and also
 /// be a valid identifier (so that core library contracts can be written for type specialized
 /// generic trait methods).
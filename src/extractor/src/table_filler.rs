@@ -6,7 +6,7 @@ use crate::converters::ConvertInto;
 use crate::mirai_utils;
 use corpus_database::{tables::Tables, types};
 use rustc::hir::{self, map::Map as HirMap, HirId};
-use rustc::ty::{self, TyCtxt};
+use rustc::ty::{self, subst::SubstsRef, TyCtxt};
 use rustc_span::hygiene::ExpnKind;
 use rustc_span::Span;
 use std::collections::HashMap;
@@ -94,6 +94,14 @@ impl<'a, 'tcx> TableFiller<'a, 'tcx> {
         self.type_registry.insert(&typ, interned_type);
         interned_type
     }
+    /// Registers the type arguments of `substs` as the generic arguments of `interned_type`.
+    fn register_generic_args(&mut self, interned_type: types::Type, substs: SubstsRef<'tcx>) {
+        for (i, arg_type) in substs.types().enumerate() {
+            let interned_arg = self.register_type(arg_type);
+            self.tables
+                .register_types_generic_args(interned_type, i.into(), interned_arg);
+        }
+    }
     pub fn register_type(&mut self, typ: ty::Ty<'tcx>) -> types::Type {
         let result = if let Some(interned_type) = self.type_registry.get(&typ) {
             *interned_type
@@ -124,6 +132,7 @@ impl<'a, 'tcx> TableFiller<'a, 'tcx> {
                         adt_def.repr.c(),
                         adt_def.is_phantom_data(),
                     );
+                    self.register_generic_args(interned_type, substs);
                     let variant_def_path = self.resolve_def_id(adt_def.did);
                     for (i, variant) in adt_def.variants.iter_enumerated() {
                         let variant_index = i.convert_into();
@@ -155,11 +164,14 @@ impl<'a, 'tcx> TableFiller<'a, 'tcx> {
                         .register_types_foreign(interned_type, foreign_def_path);
                     interned_type
                 }
-                ty::TyKind::Array(element_type, _len) => {
+                ty::TyKind::Array(element_type, len) => {
                     let interned_type = self.insert_new_type_into_table("Array", typ);
                     let element_interned_type = self.register_type(element_type);
                     self.tables
                         .register_types_array(interned_type, element_interned_type);
+                    if let Some(len) = len.try_eval_usize(self.tcx, ty::ParamEnv::reveal_all()) {
+                        self.tables.register_types_array_len(interned_type, len);
+                    }
                     interned_type
                 }
                 ty::TyKind::Slice(element_type) => {
@@ -189,16 +201,26 @@ impl<'a, 'tcx> TableFiller<'a, 'tcx> {
                     );
                     interned_type
                 }
-                ty::TyKind::FnDef(def_id, _substs) => {
+                ty::TyKind::FnDef(def_id, substs) => {
                     let interned_type = self.insert_new_type_into_table("FnDef", typ);
                     let fn_def_path = self.resolve_def_id(def_id);
                     self.tables
                         .register_types_fn_def(interned_type, fn_def_path);
+                    self.register_generic_args(interned_type, substs);
                     interned_type
                 }
-                ty::TyKind::FnPtr(_fn_sig) => {
+                ty::TyKind::FnPtr(fn_sig) => {
                     let interned_type = self.insert_new_type_into_table("FnPtr", typ);
                     self.tables.register_types_fn_ptr(interned_type);
+                    let fn_sig = fn_sig.skip_binder();
+                    for (i, param_type) in fn_sig.inputs().iter().enumerate() {
+                        let interned_param = self.register_type(*param_type);
+                        self.tables
+                            .register_types_fn_ptr_param(interned_type, i.into(), interned_param);
+                    }
+                    let interned_output = self.register_type(fn_sig.output());
+                    self.tables
+                        .register_types_fn_ptr_output(interned_type, interned_output);
                     interned_type
                 }
                 ty::TyKind::Dynamic(binder, _region) => {
@@ -210,9 +232,16 @@ impl<'a, 'tcx> TableFiller<'a, 'tcx> {
                                 let def_path = self.resolve_def_id(trait_ref.def_id);
                                 self.tables
                                     .register_types_dynamic_trait(interned_type, def_path);
+                                self.register_generic_args(interned_type, trait_ref.substs);
                             }
-                            ty::ExistentialPredicate::Projection(_projection) => {
-                                // TODO
+                            ty::ExistentialPredicate::Projection(projection) => {
+                                let item = self.resolve_def_id(projection.item_def_id);
+                                let bound_type = self.register_type(projection.ty);
+                                self.tables.register_types_dynamic_projection(
+                                    interned_type,
+                                    item,
+                                    bound_type,
+                                );
                             }
                             ty::ExistentialPredicate::AutoTrait(def_id) => {
                                 let def_path = self.resolve_def_id(*def_id);
@@ -265,12 +294,14 @@ impl<'a, 'tcx> TableFiller<'a, 'tcx> {
                         trait_def_path,
                         trait_item,
                     );
+                    self.register_generic_args(interned_type, projection.substs);
                     interned_type
                 }
-                ty::TyKind::Opaque(def_id, _substs) => {
+                ty::TyKind::Opaque(def_id, substs) => {
                     let interned_type = self.insert_new_type_into_table("Opaque", typ);
                     let def_path = self.resolve_def_id(def_id);
                     self.tables.register_types_opaque(interned_type, def_path);
+                    self.register_generic_args(interned_type, substs);
                     interned_type
                 }
                 ty::TyKind::Param(param_ty) => {
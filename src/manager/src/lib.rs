@@ -65,3 +65,17 @@ pub fn update_database(workspace: &Path, database_root: &Path) {
     let mut manager = DatabaseManager::new(database_root);
     manager.update_database(workspace);
 }
+
+/// Export the database as Soufflé facts and declarations.
+#[logfn(Trace)]
+pub fn export_souffle(database_root: &Path, output_dir: &Path, resolve_interned: bool) {
+    let tables = corpus_database::tables::Tables::load_multifile(database_root).unwrap();
+    tables.store_souffle(output_dir, resolve_interned).unwrap();
+}
+
+/// Export the database into a single SQLite file.
+#[logfn(Trace)]
+pub fn export_sqlite(database_root: &Path, output_file: &Path) {
+    let tables = corpus_database::tables::Tables::load_multifile(database_root).unwrap();
+    tables.store_sqlite(output_file).unwrap();
+}
@@ -94,6 +94,37 @@ enum Command {
         about = "Scan the compiled crates and update the database."
     )]
     UpdateDatabase,
+    #[structopt(
+        name = "export-souffle",
+        about = "Export the database as Soufflé facts together with their declarations."
+    )]
+    ExportSouffle {
+        #[structopt(
+            parse(from_os_str),
+            default_value = "../souffle",
+            long = "output",
+            help = "The directory to which the facts are written."
+        )]
+        output: PathBuf,
+        #[structopt(
+            long = "resolve-interned",
+            help = "Write the interned ids that stand for strings as the strings themselves."
+        )]
+        resolve_interned: bool,
+    },
+    #[structopt(
+        name = "export-sqlite",
+        about = "Export the database into a single SQLite file."
+    )]
+    ExportSqlite {
+        #[structopt(
+            parse(from_os_str),
+            default_value = "../corpus.sqlite",
+            long = "output",
+            help = "The SQLite file to which the database is written. It is replaced if it exists."
+        )]
+        output: PathBuf,
+    },
 }
 
 fn main() {
@@ -169,5 +200,12 @@ fn main() {
         Command::UpdateDatabase => {
             corpus_manager::update_database(&args.workspace, &args.database_root)
         }
+        Command::ExportSouffle {
+            output,
+            resolve_interned,
+        } => corpus_manager::export_souffle(&args.database_root, &output, resolve_interned),
+        Command::ExportSqlite { output } => {
+            corpus_manager::export_sqlite(&args.database_root, &output)
+        }
     }
 }